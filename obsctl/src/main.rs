@@ -1,8 +1,8 @@
 use anyhow::{bail, Context, Result};
-use open_build_service_api::{Client, PackageCode, ResultListResult};
+use futures::prelude::*;
+use open_build_service_api::{Client, PackageCode, ResultListResult, ResultOptions};
 use oscrc::Oscrc;
 use std::path::PathBuf;
-use std::time::Duration;
 use structopt::StructOpt;
 use url::Url;
 
@@ -16,24 +16,29 @@ struct Package {
 struct MonitorData {
     repository: String,
     arch: String,
+    package: String,
     code: PackageCode,
 }
 
 impl MonitorData {
-    fn from_result(r: ResultListResult, package: &str) -> Self {
-        let s = r
-            .get_status(package)
-            .expect("No status for current package");
-        let code = if r.dirty {
-            PackageCode::Unknown
-        } else {
-            s.code
-        };
-        MonitorData {
-            repository: r.repository,
-            arch: r.arch,
-            code,
-        }
+    /// Data for the package and its multibuild flavors, empty if the server
+    /// doesn't report a status for them (yet)
+    fn from_result(r: &ResultListResult, package: &str) -> Vec<Self> {
+        r.get_package_statuses(package)
+            .map(|s| {
+                let code = if r.dirty {
+                    PackageCode::Unknown
+                } else {
+                    s.code
+                };
+                MonitorData {
+                    repository: r.repository.clone(),
+                    arch: r.arch.clone(),
+                    package: s.package.clone(),
+                    code,
+                }
+            })
+            .collect()
     }
 }
 
@@ -44,29 +49,39 @@ async fn monitor(client: Client, opts: Package) -> Result<()> {
     );
     let p = client.project(opts.project).package(opts.package.clone());
     let mut last: Vec<MonitorData> = Vec::new();
-    loop {
-        let result = p.result().await?;
-        for r in result.results {
-            let data = MonitorData::from_result(r, &opts.package);
-
-            if let Some(old) = last
-                .iter_mut()
-                .find(|m| m.repository == data.repository && m.arch == data.arch)
-            {
-                if data.code != PackageCode::Unknown && old.code != data.code {
-                    println!(" * {} {} => {}", data.repository, data.arch, data.code);
-                    *old = data;
+    let options = ResultOptions {
+        multibuild: true,
+        ..Default::default()
+    };
+    let mut results = p.watch(&options)?;
+    while let Some(result) = results.try_next().await? {
+        for r in &result.results {
+            for data in MonitorData::from_result(r, &opts.package) {
+                if let Some(old) = last.iter_mut().find(|m| {
+                    m.repository == data.repository
+                        && m.arch == data.arch
+                        && m.package == data.package
+                }) {
+                    if data.code != PackageCode::Unknown && old.code != data.code {
+                        println!(
+                            " * {} {} {} => {}",
+                            data.package, data.repository, data.arch, data.code
+                        );
+                        *old = data;
+                    }
+                } else {
+                    println!(
+                        "* {} {} {} => {}",
+                        data.package, data.repository, data.arch, data.code
+                    );
+                    last.push(data);
                 }
-            } else {
-                println!("* {} {} => {}", data.repository, data.arch, data.code);
-                last.push(data);
             }
         }
 
-        if last.iter().all(|m| m.code.is_final()) {
+        if !last.is_empty() && last.iter().all(|m| m.code.is_final()) {
             break;
         }
-        tokio::time::sleep(Duration::from_secs(20)).await;
     }
 
     if last
@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use futures::prelude::*;
-use open_build_service_api::Client;
+use open_build_service_api::{Client, FileOptions};
 use oscrc::Oscrc;
 use tokio::io::AsyncWriteExt;
 use std::path::PathBuf;
@@ -21,6 +21,22 @@ struct Package {
     package: String,
 }
 
+#[derive(StructOpt, Debug)]
+struct PackageFile {
+    project: String,
+    package: String,
+    file: String,
+    #[structopt(long)]
+    rev: Option<String>,
+    #[structopt(long)]
+    expand: bool,
+}
+
+#[derive(StructOpt, Debug)]
+struct Project {
+    project: String,
+}
+
 #[derive(StructOpt, Debug)]
 struct BuildResult {
     project: String,
@@ -33,6 +49,16 @@ async fn jobstatus(client: Client, opts: PackageFull) -> Result<()> {
     Ok(())
 }
 
+async fn jobhistory(client: Client, opts: PackageFull) -> Result<()> {
+    let p = client.project(opts.project).package(opts.package);
+    println!(
+        "{:#?}",
+        p.jobhistory(&opts.repository, &opts.arch, &Default::default())
+            .await
+    );
+    Ok(())
+}
+
 async fn status(client: Client, opts: PackageFull) -> Result<()> {
     let p = client.project(opts.project).package(opts.package);
     println!("{:#?}", p.status(&opts.repository, &opts.arch).await);
@@ -45,6 +71,27 @@ async fn history(client: Client, opts: PackageFull) -> Result<()> {
     Ok(())
 }
 
+async fn projects(client: Client) -> Result<()> {
+    for project in client.projects().await? {
+        println!("{}", project);
+    }
+    Ok(())
+}
+
+async fn packages(client: Client, opts: Project) -> Result<()> {
+    let p = client.project(opts.project);
+    for package in p.packages().await? {
+        println!("{}", package);
+    }
+    Ok(())
+}
+
+async fn source_history(client: Client, opts: Package) -> Result<()> {
+    let p = client.project(opts.project).package(opts.package);
+    println!("{:#?}", p.source_history().await);
+    Ok(())
+}
+
 async fn log(client: Client, opts: PackageFull) -> Result<()> {
     let p = client.project(opts.project).package(opts.package);
     let log = p.log(&opts.repository, &opts.arch);
@@ -62,12 +109,53 @@ async fn log(client: Client, opts: PackageFull) -> Result<()> {
     Ok(())
 }
 
+async fn binaries(client: Client, opts: PackageFull) -> Result<()> {
+    let p = client.project(opts.project).package(opts.package);
+    println!("{:#?}", p.binaries(&opts.repository, &opts.arch).await);
+    Ok(())
+}
+
+async fn buildinfo(client: Client, opts: PackageFull) -> Result<()> {
+    let p = client.project(opts.project).package(opts.package);
+    println!("{:#?}", p.buildinfo(&opts.repository, &opts.arch).await);
+    Ok(())
+}
+
 async fn list(client: Client, opts: Package) -> Result<()> {
     let p = client.project(opts.project).package(opts.package);
     println!("{:#?}", p.list().await);
     Ok(())
 }
 
+async fn cat(client: Client, opts: PackageFile) -> Result<()> {
+    let p = client.project(opts.project).package(opts.package);
+    let options = FileOptions {
+        rev: opts.rev,
+        expand: opts.expand,
+    };
+
+    let mut stdout = tokio::io::stdout();
+
+    let mut stream = p.file(&opts.file, &options)?;
+    while let Some(chunk) = stream.try_next().await? {
+        stdout.write_all(&chunk).await?;
+    }
+
+    Ok(())
+}
+
+async fn meta(client: Client, opts: BuildResult) -> Result<()> {
+    let p = client.project(opts.project);
+    if let Some(package) = opts.package {
+        let p = p.package(package);
+        println!("{:#?}", p.meta().await);
+    } else {
+        println!("{:#?}", p.meta().await);
+    }
+
+    Ok(())
+}
+
 async fn result(client: Client, opts: BuildResult) -> Result<()> {
     let p = client.project(opts.project);
     if let Some(package) = opts.package {
@@ -84,9 +172,17 @@ async fn result(client: Client, opts: BuildResult) -> Result<()> {
 enum Command {
     Jobstatus(PackageFull),
     History(PackageFull),
+    Jobhistory(PackageFull),
     Status(PackageFull),
     Log(PackageFull),
+    Binaries(PackageFull),
+    Buildinfo(PackageFull),
+    Projects,
+    Packages(Project),
     List(Package),
+    SourceHistory(Package),
+    Cat(PackageFile),
+    Meta(BuildResult),
     Result(BuildResult),
 }
 
@@ -135,8 +231,16 @@ async fn main() -> Result<()> {
         Command::Jobstatus(o) => jobstatus(client, o).await,
         Command::Status(o) => status(client, o).await,
         Command::History(o) => history(client, o).await,
+        Command::Jobhistory(o) => jobhistory(client, o).await,
+        Command::Projects => projects(client).await,
+        Command::Packages(o) => packages(client, o).await,
         Command::List(o) => list(client, o).await,
+        Command::SourceHistory(o) => source_history(client, o).await,
         Command::Log(o) => log(client, o).await,
+        Command::Binaries(o) => binaries(client, o).await,
+        Command::Buildinfo(o) => buildinfo(client, o).await,
+        Command::Cat(o) => cat(client, o).await,
+        Command::Meta(o) => meta(client, o).await,
         Command::Result(o) => result(client, o).await,
     }
 }
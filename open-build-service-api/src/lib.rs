@@ -1,12 +1,18 @@
 use bytes::Bytes;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use futures::future::BoxFuture;
 use futures::prelude::*;
 use futures::ready;
-use futures::stream::BoxStream;
+use futures::stream::{self, BoxStream};
 use quick_xml::de::DeError;
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use quick_xml::events::{BytesStart, Event};
+use serde::ser::SerializeSeq;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashSet, VecDeque};
+use std::ops::Range;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use thiserror::Error;
 use url::Url;
 use reqwest::{RequestBuilder, Response};
@@ -19,12 +25,52 @@ pub enum Error {
     DeError(#[from] DeError),
     #[error("{0}")]
     ApiError(ApiError),
+    #[error("Other repositories depend on this: {0}")]
+    RepoDependency(ApiError),
+    #[error("Delete failed: {0}")]
+    DeleteError(ApiError),
+    #[error("Not permitted to change the request: {0}")]
+    RequestNoPermission(ApiError),
+    #[error("Request can't be changed anymore: {0}")]
+    RequestNotModifiable(ApiError),
+    #[error("No matching review: {0}")]
+    ReviewNotSpecified(ApiError),
     #[error("Unexpected result")]
     UnexpectedResult,
     #[error("Invalid client url")]
     InvalidUrl,
 }
 
+impl Error {
+    /// The error returned by the server, if any
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Error::ApiError(e)
+            | Error::RepoDependency(e)
+            | Error::DeleteError(e)
+            | Error::RequestNoPermission(e)
+            | Error::RequestNotModifiable(e)
+            | Error::ReviewNotSpecified(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ApiError> for Error {
+    fn from(e: ApiError) -> Self {
+        match e.code.as_str() {
+            "repo_dependency" => Error::RepoDependency(e),
+            "delete_error" => Error::DeleteError(e),
+            "post_request_no_permission" | "review_change_state_no_permission" => {
+                Error::RequestNoPermission(e)
+            }
+            "request_not_modifiable" => Error::RequestNotModifiable(e),
+            "review_not_specified" => Error::ReviewNotSpecified(e),
+            _ => Error::ApiError(e),
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, Debug)]
 pub struct ApiErrorSummary {
     #[serde(rename = "$value")]
@@ -43,8 +89,323 @@ impl std::fmt::Display for ApiError {
     }
 }
 
+#[derive(Clone, Deserialize, Debug)]
+pub struct StatusData {
+    pub name: String,
+    #[serde(rename = "$value", default)]
+    pub value: String,
+}
+
+/// Generic response of commands which don't return a specific document
+#[derive(Clone, Deserialize, Debug)]
+pub struct Status {
+    pub code: String,
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub data: Vec<StatusData>,
+}
+
+impl Status {
+    pub fn data(&self, name: &str) -> Option<&str> {
+        self.data
+            .iter()
+            .find(|d| d.name == name)
+            .map(|d| d.value.as_str())
+    }
+}
+
 type Result<T> = std::result::Result<T, Error>;
 
+/// Serialize a value as a child element rather than an attribute
+fn serialize_element<T, S>(value: &T, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    T: Serialize + ?Sized,
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct("", value)
+}
+
+/// Serialize a list of values as a sequence of child elements
+fn serialize_elements<T, S>(values: &[T], serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    struct Element<'a, T>(&'a T);
+
+    impl<T: Serialize> Serialize for Element<'_, T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+            serialize_element(self.0, serializer)
+        }
+    }
+
+    let mut seq = serializer.serialize_seq(Some(values.len()))?;
+    for v in values {
+        seq.serialize_element(&Element(v))?;
+    }
+    seq.end()
+}
+
+/// Child element of a document which isn't covered by its typed model
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct UnknownElement {
+    name: String,
+    xml: String,
+    /// Names of the known elements following this one in the original document
+    following: Vec<String>,
+}
+
+/// Attributes and child elements of an element which aren't covered by its
+/// typed model
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct UnknownContent {
+    /// Attribute names with their raw, still escaped, values
+    attributes: Vec<(String, String)>,
+    elements: Vec<UnknownElement>,
+}
+
+fn element_name(e: &BytesStart) -> std::result::Result<String, DeError> {
+    let name = std::str::from_utf8(e.name()).map_err(quick_xml::Error::from)?;
+    Ok(name.to_owned())
+}
+
+fn unknown_attributes(
+    e: &BytesStart,
+    known: &[&str],
+) -> std::result::Result<Vec<(String, String)>, DeError> {
+    let mut attributes = Vec::new();
+    for a in e.attributes() {
+        let a = a?;
+        let key = std::str::from_utf8(a.key).map_err(quick_xml::Error::from)?;
+        if !known.contains(&key) {
+            let value = std::str::from_utf8(&a.value).map_err(quick_xml::Error::from)?;
+            attributes.push((key.to_owned(), value.to_owned()));
+        }
+    }
+    Ok(attributes)
+}
+
+/// Byte ranges of the children of the root element which are named `name`
+fn child_element_ranges(xml: &str, name: &str) -> std::result::Result<Vec<Range<usize>>, DeError> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut buf = Vec::new();
+    let mut depth = 0;
+    let mut ranges = Vec::new();
+    let mut start = None;
+
+    loop {
+        let pos = reader.buffer_position();
+        match reader.read_event(&mut buf)? {
+            Event::Start(ref e) => {
+                depth += 1;
+                if depth == 2 && e.name() == name.as_bytes() {
+                    start = Some(pos);
+                }
+            }
+            Event::Empty(ref e) if depth == 1 && e.name() == name.as_bytes() => {
+                ranges.push(pos..reader.buffer_position());
+            }
+            Event::End(_) => {
+                depth -= 1;
+                if depth == 1 {
+                    if let Some(start) = start.take() {
+                        ranges.push(start..reader.buffer_position());
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+
+    Ok(ranges)
+}
+
+impl UnknownContent {
+    /// Collect the attributes and children of the root element of `xml`
+    /// whose names aren't in `known_attributes` and `known_elements`
+    fn from_xml(
+        xml: &str,
+        known_attributes: &[&str],
+        known_elements: &[&str],
+    ) -> std::result::Result<Self, DeError> {
+        let mut reader = quick_xml::Reader::from_str(xml);
+        let mut buf = Vec::new();
+        let mut depth = 0;
+        let mut unknown = UnknownContent::default();
+        // Start of the unknown element currently being read
+        let mut start = None;
+
+        loop {
+            let pos = reader.buffer_position();
+            let (child, empty) = match reader.read_event(&mut buf)? {
+                Event::Start(ref e) => {
+                    depth += 1;
+                    if depth == 1 {
+                        unknown.attributes = unknown_attributes(e, known_attributes)?;
+                        (None, false)
+                    } else if depth == 2 {
+                        (Some(element_name(e)?), false)
+                    } else {
+                        (None, false)
+                    }
+                }
+                Event::Empty(ref e) if depth == 0 => {
+                    unknown.attributes = unknown_attributes(e, known_attributes)?;
+                    (None, false)
+                }
+                Event::Empty(ref e) if depth == 1 => (Some(element_name(e)?), true),
+                Event::End(_) => {
+                    depth -= 1;
+                    if depth == 1 {
+                        if let Some((start, name)) = start.take() {
+                            unknown.elements.push(UnknownElement {
+                                name,
+                                xml: xml[start..reader.buffer_position()].to_owned(),
+                                following: Vec::new(),
+                            });
+                        }
+                    }
+                    (None, false)
+                }
+                Event::Eof => break,
+                _ => (None, false),
+            };
+            buf.clear();
+
+            if let Some(name) = child {
+                if known_elements.contains(&name.as_str()) {
+                    for u in &mut unknown.elements {
+                        if !u.following.contains(&name) {
+                            u.following.push(name.clone());
+                        }
+                    }
+                } else if empty {
+                    unknown.elements.push(UnknownElement {
+                        name,
+                        xml: xml[pos..reader.buffer_position()].to_owned(),
+                        following: Vec::new(),
+                    });
+                } else {
+                    start = Some((pos, name));
+                }
+            }
+        }
+
+        Ok(unknown)
+    }
+
+    /// Insert the unknown content into a serialized document with root
+    /// element `root`.
+    ///
+    /// Elements go in front of the first known element which followed them in
+    /// their original document, or which comes after such an element in
+    /// `known_elements`, expected to be in schema order.
+    fn insert(
+        &self,
+        xml: String,
+        root: &str,
+        known_elements: &[&str],
+    ) -> std::result::Result<String, DeError> {
+        let xml = self.insert_attributes(xml);
+        if self.elements.is_empty() {
+            return Ok(xml);
+        }
+
+        let xml = match xml.strip_suffix("/>") {
+            Some(open) => format!("{}></{}>", open, root),
+            None => xml,
+        };
+
+        let mut positions: Vec<(String, usize)> = Vec::new();
+        let mut reader = quick_xml::Reader::from_str(&xml);
+        let mut buf = Vec::new();
+        let mut depth = 0;
+        loop {
+            let pos = reader.buffer_position();
+            let child = match reader.read_event(&mut buf)? {
+                Event::Start(ref e) => {
+                    depth += 1;
+                    if depth == 2 {
+                        Some(element_name(e)?)
+                    } else {
+                        None
+                    }
+                }
+                Event::Empty(ref e) if depth == 1 => Some(element_name(e)?),
+                Event::End(_) => {
+                    depth -= 1;
+                    None
+                }
+                Event::Eof => break,
+                _ => None,
+            };
+            buf.clear();
+
+            if let Some(name) = child {
+                if !positions.iter().any(|(n, _)| *n == name) {
+                    positions.push((name, pos));
+                }
+            }
+        }
+
+        let end = xml.len() - root.len() - 3;
+        let rank = |name: &str| known_elements.iter().position(|k| *k == name);
+        let mut inserts: Vec<(usize, &str)> = self
+            .elements
+            .iter()
+            .map(|e| {
+                let first = e.following.first().and_then(|f| rank(f));
+                let pos = positions
+                    .iter()
+                    .find(|(n, _)| {
+                        e.following.contains(n)
+                            || matches!((rank(n), first), (Some(r), Some(f)) if r >= f)
+                    })
+                    .map_or(end, |(_, pos)| *pos);
+                (pos, e.xml.as_str())
+            })
+            .collect();
+        inserts.sort_by_key(|(pos, _)| *pos);
+
+        let mut out =
+            String::with_capacity(xml.len() + inserts.iter().map(|(_, e)| e.len()).sum::<usize>());
+        let mut last = 0;
+        for (pos, element) in inserts {
+            out.push_str(&xml[last..pos]);
+            out.push_str(element);
+            last = pos;
+        }
+        out.push_str(&xml[last..]);
+
+        Ok(out)
+    }
+
+    fn remove_elements(&mut self, name: &str) {
+        self.elements.retain(|e| e.name != name);
+    }
+
+    fn insert_attributes(&self, mut xml: String) -> String {
+        if self.attributes.is_empty() {
+            return xml;
+        }
+
+        // Attribute values are escaped, so the first '>' closes the root tag
+        let mut pos = xml.find('>').unwrap_or(xml.len());
+        if xml[..pos].ends_with('/') {
+            pos -= 1;
+        }
+        let attributes: String = self
+            .attributes
+            .iter()
+            .map(|(key, value)| format!(" {}=\"{}\"", key, value.replace('"', "&quot;")))
+            .collect();
+        xml.insert_str(pos, &attributes);
+        xml
+    }
+}
+
 #[derive(Copy, Clone, Deserialize, Debug, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RepositoryCode {
@@ -122,121 +483,1869 @@ pub struct BuildStatus {
     pub details: Option<String>,
 }
 
+impl BuildStatus {
+    /// The package name without the multibuild flavor
+    pub fn base_package(&self) -> &str {
+        match self.package.split_once(':') {
+            Some((package, _)) => package,
+            None => &self.package,
+        }
+    }
+
+    /// The multibuild flavor, if any
+    pub fn flavor(&self) -> Option<&str> {
+        self.package.split_once(':').map(|(_, flavor)| flavor)
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobHistoryCode {
+    Succeeded,
+    Failed,
+    /// Built successfully, but the result was identical to the previous build
+    Unchanged,
+    #[serde(other)]
+    Unknown,
+}
+
+impl std::fmt::Display for JobHistoryCode {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        self.serialize(fmt)
+    }
+}
+
 #[derive(Deserialize, Debug)]
-pub struct BuildHistoryEntry {
+pub struct JobHistoryEntry {
+    pub package: String,
     pub rev: String,
     pub srcmd5: String,
     pub versrel: String,
     pub bcnt: String,
-    pub time: String,
+    pub readytime: u64,
+    pub starttime: u64,
+    pub endtime: u64,
+    pub code: JobHistoryCode,
+    pub uri: Option<String>,
+    pub workerid: Option<String>,
+    pub hostarch: Option<String>,
+    pub reason: Option<String>,
+    pub verifymd5: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct BuildHistory {
-    #[serde(rename = "entry")]
-    pub entries: Vec<BuildHistoryEntry>,
+impl JobHistoryEntry {
+    /// Time spent waiting for a worker
+    pub fn wait_time(&self) -> Duration {
+        Duration::from_secs(self.starttime.saturating_sub(self.readytime))
+    }
+
+    pub fn build_time(&self) -> Duration {
+        Duration::from_secs(self.endtime.saturating_sub(self.starttime))
+    }
 }
 
 #[derive(Deserialize, Debug)]
-pub struct DirectoryEntry {
-    pub name: String,
-    pub size: u64,
-    pub md5: String,
-    pub mtime: u64,
-    pub originproject: Option<String>,
-    //available ?
-    //recommended ?
-    pub hash: Option<String>,
+pub struct JobHistory {
+    #[serde(rename = "jobhist", default)]
+    pub entries: Vec<JobHistoryEntry>,
 }
 
 #[derive(Deserialize, Debug)]
-pub struct Directory {
-    pub name: String,
-    pub rev: String,
-    pub vrev: String,
-    pub srcmd5: String,
-    #[serde(rename = "entry")]
-    pub entries: Vec<DirectoryEntry>,
+pub struct Worker {
+    pub workerid: String,
+    pub hostarch: String,
+    pub uri: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
-pub struct ResultListResult {
+pub struct BuildingWorker {
+    pub workerid: String,
+    pub hostarch: String,
+    pub uri: Option<String>,
     pub project: String,
     pub repository: String,
+    pub package: String,
     pub arch: String,
-    pub code: RepositoryCode,
-    #[serde(default)]
-    pub dirty: bool,
-    #[serde(rename = "status")]
-    pub statusses: Vec<BuildStatus>,
+    pub starttime: u64,
+    pub jobid: Option<String>,
 }
 
-impl ResultListResult {
-    pub fn get_status(&self, package: &str) -> Option<&BuildStatus> {
-        self.statusses.iter().find(|s| s.package == package)
-    }
+/// Number of jobs for an architecture
+#[derive(Deserialize, Debug)]
+pub struct JobQueue {
+    pub arch: String,
+    pub jobs: u64,
 }
 
 #[derive(Deserialize, Debug)]
-pub struct ResultList {
+pub struct BuildAverage {
+    pub arch: String,
+    pub buildavg: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SchedulerQueue {
+    pub high: u64,
+    pub med: u64,
+    pub low: u64,
+    pub next: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Daemon {
+    #[serde(rename = "type")]
+    pub daemon_type: String,
+    /// Only set for schedulers
+    pub arch: Option<String>,
     pub state: String,
-    #[serde(rename = "result")]
-    pub results: Vec<ResultListResult>,
+    pub starttime: Option<u64>,
+    pub queue: Option<SchedulerQueue>,
 }
 
 #[derive(Deserialize, Debug)]
-struct LogEntryEntry {
-    size: usize,
-    mtime: u64,
+pub struct Partition {
+    pub name: Option<String>,
+    #[serde(rename = "daemon", default)]
+    pub daemons: Vec<Daemon>,
 }
 
+/// Scheduler state reported outside of a partition
 #[derive(Deserialize, Debug)]
-struct LogEntry {
+pub struct SchedulerStatus {
+    pub arch: String,
+    pub state: String,
+    pub starttime: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WorkerStatus {
+    pub clients: Option<u64>,
+    #[serde(default)]
+    pub idle: Vec<Worker>,
+    #[serde(default)]
+    pub building: Vec<BuildingWorker>,
+    #[serde(default)]
+    pub away: Vec<Worker>,
+    #[serde(default)]
+    pub down: Vec<Worker>,
+    #[serde(default)]
+    pub dead: Vec<Worker>,
+    #[serde(default)]
+    pub waiting: Vec<JobQueue>,
+    #[serde(default)]
+    pub blocked: Vec<JobQueue>,
+    #[serde(default)]
+    pub buildavg: Vec<BuildAverage>,
+    #[serde(rename = "partition", default)]
+    pub partitions: Vec<Partition>,
+    #[serde(rename = "scheduler", default)]
+    pub schedulers: Vec<SchedulerStatus>,
+}
+
+impl WorkerStatus {
+    /// Number of jobs waiting for a worker of the given architecture
+    pub fn waiting_jobs(&self, arch: &str) -> u64 {
+        self.waiting
+            .iter()
+            .find(|q| q.arch == arch)
+            .map_or(0, |q| q.jobs)
+    }
+
+    /// Number of jobs waiting for their dependencies on the given architecture
+    pub fn blocked_jobs(&self, arch: &str) -> u64 {
+        self.blocked
+            .iter()
+            .find(|q| q.arch == arch)
+            .map_or(0, |q| q.jobs)
+    }
+
+    /// Idle workers running on the given host architecture, which doesn't
+    /// include e.g. x86_64 workers for i586 jobs
+    pub fn idle_workers_by_hostarch<'a>(
+        &'a self,
+        hostarch: &'a str,
+    ) -> impl Iterator<Item = &'a Worker> + 'a {
+        self.idle.iter().filter(move |w| w.hostarch == hostarch)
+    }
+
+    /// Scheduler daemons of all partitions
+    pub fn scheduler_daemons(&self) -> impl Iterator<Item = &Daemon> {
+        self.partitions
+            .iter()
+            .flat_map(|p| p.daemons.iter())
+            .filter(|d| d.daemon_type == "scheduler")
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BuildHistoryEntry {
+    pub rev: String,
+    pub srcmd5: String,
+    pub versrel: String,
+    pub bcnt: String,
+    pub time: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BuildHistory {
     #[serde(rename = "entry")]
-    pub entries: Vec<LogEntryEntry>,
+    pub entries: Vec<BuildHistoryEntry>,
 }
 
-enum PackageLogRequest {
-    Initial,
-    Request(BoxFuture<'static, Result<Response>>),
-    Stream((BoxStream<'static, reqwest::Result<Bytes>>, bool)),
+#[derive(Deserialize, Debug)]
+pub struct Revision {
+    pub rev: String,
+    pub vrev: Option<String>,
+    pub srcmd5: String,
+    pub version: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub time: DateTime<Utc>,
+    pub user: Option<String>,
+    pub comment: Option<String>,
+    pub requestid: Option<u64>,
 }
 
-pub struct PackageLogStream<'a> {
-    client: &'a Client,
-    url: Url,
-    offset: usize,
-    request: PackageLogRequest,
+#[derive(Deserialize, Debug)]
+pub struct SourceRevisionList {
+    #[serde(rename = "revision", default)]
+    pub revisions: Vec<Revision>,
 }
 
-impl<'a> PackageLogStream<'a> {
-    fn new(client: &'a Client, offset: usize, url: Url) -> Self {
-        Self {
-            client,
-            url,
-            offset,
-            request: PackageLogRequest::Initial,
-        }
+impl SourceRevisionList {
+    /// Find the revision with the given source md5, e.g. from a `BuildHistoryEntry`
+    pub fn find_srcmd5(&self, srcmd5: &str) -> Option<&Revision> {
+        self.revisions.iter().find(|r| r.srcmd5 == srcmd5)
     }
 
-    fn request_log(&self, offset: usize) -> Result<Url> {
-        let mut url = self.url.clone();
-        url.query_pairs_mut()
-            .append_pair("nostream", "1")
-            .append_pair("start", &format!("{}", offset));
-        Ok(url)
+    pub fn latest(&self) -> Option<&Revision> {
+        self.revisions.last()
     }
+}
 
+#[derive(Deserialize, Debug)]
+pub struct BinaryEntry {
+    pub filename: String,
+    pub size: u64,
+    pub mtime: u64,
 }
 
-impl Stream for PackageLogStream<'_> {
-    type Item = Result<Bytes>;
+#[derive(Deserialize, Debug)]
+pub struct BinaryList {
+    /// Package the binaries belong to, only set in build results
+    pub package: Option<String>,
+    #[serde(rename = "binary", default)]
+    pub binaries: Vec<BinaryEntry>,
+}
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let me = self.get_mut();
+#[derive(Deserialize, Debug)]
+pub struct BuildDependency {
+    pub name: String,
+    pub epoch: Option<String>,
+    pub version: Option<String>,
+    pub release: Option<String>,
+    pub arch: Option<String>,
+    pub project: Option<String>,
+    pub repository: Option<String>,
+    pub hdrmd5: Option<String>,
+    /// Installed before the build environment is set up
+    #[serde(default)]
+    pub preinstall: bool,
+    /// Installed in the VM before the build environment is set up
+    #[serde(default)]
+    pub vminstall: bool,
+    /// Package scripts have to run on installation
+    #[serde(default)]
+    pub runscripts: bool,
+    /// Not part of the meta data deciding whether a rebuild is needed
+    #[serde(default)]
+    pub notmeta: bool,
+    #[serde(default)]
+    pub noinstall: bool,
+    #[serde(default)]
+    pub installonly: bool,
+}
 
-        loop {
-            match me.request {
+#[derive(Deserialize, Debug)]
+pub struct BuildInfo {
+    pub project: String,
+    pub repository: String,
+    pub package: Option<String>,
+    pub arch: String,
+    pub hostarch: Option<String>,
+    pub srcmd5: Option<String>,
+    pub verifymd5: Option<String>,
+    pub rev: Option<String>,
+    pub disturl: Option<String>,
+    /// Build recipe, e.g. the spec or dsc file
+    pub file: Option<String>,
+    pub versrel: Option<String>,
+    pub bcnt: Option<u64>,
+    pub release: Option<String>,
+    #[serde(default)]
+    pub debuginfo: bool,
+    #[serde(rename = "subpack", default)]
+    pub subpacks: Vec<String>,
+    #[serde(rename = "bdep", default)]
+    pub bdeps: Vec<BuildDependency>,
+    #[serde(rename = "path", default)]
+    pub paths: Vec<RepositoryPath>,
+    /// Set if no build is possible, e.g. due to unresolvable dependencies
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BuildDepInfoPackage {
+    pub name: String,
+    pub source: Option<String>,
+    /// Dependencies of the package, binary names by default or package names
+    /// for the `PkgNames` and `RevPkgNames` views; for the latter these are
+    /// the packages depending on this one
+    #[serde(default)]
+    pub pkgdep: Vec<String>,
+    /// Binaries produced by the package
+    #[serde(default)]
+    pub subpkg: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BuildDepInfoCycle {
+    #[serde(rename = "package", default)]
+    pub packages: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BuildDepInfo {
+    #[serde(rename = "package", default)]
+    pub packages: Vec<BuildDepInfoPackage>,
+    #[serde(rename = "cycle", default)]
+    pub cycles: Vec<BuildDepInfoCycle>,
+}
+
+impl BuildDepInfo {
+    pub fn get(&self, package: &str) -> Option<&BuildDepInfoPackage> {
+        self.packages.iter().find(|p| p.name == package)
+    }
+
+    /// All packages reachable from `package` by following `pkgdep`, i.e. the
+    /// transitive dependencies for the `PkgNames` view or the transitive
+    /// reverse dependencies for the `RevPkgNames` view
+    pub fn transitive_pkgdeps(&self, package: &str) -> Vec<String> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<&str> = VecDeque::new();
+        let mut result = Vec::new();
+
+        seen.insert(package);
+        queue.push_back(package);
+        while let Some(current) = queue.pop_front() {
+            let deps = match self.get(current) {
+                Some(p) => &p.pkgdep,
+                None => continue,
+            };
+            for dep in deps {
+                if seen.insert(dep) {
+                    result.push(dep.clone());
+                    queue.push_back(dep);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DirectoryEntry {
+    pub name: String,
+    pub size: u64,
+    pub md5: String,
+    pub mtime: u64,
+    pub originproject: Option<String>,
+    //available ?
+    //recommended ?
+    pub hash: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LinkInfo {
+    pub project: String,
+    pub package: String,
+    pub srcmd5: String,
+    pub baserev: Option<String>,
+    pub xsrcmd5: Option<String>,
+    pub lsrcmd5: Option<String>,
+    /// Set if the link couldn't be expanded, e.g. due to conflicts
+    pub error: Option<String>,
+}
+
+impl LinkInfo {
+    pub fn is_broken(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceCode {
+    Running,
+    Failed,
+    Succeeded,
+}
+
+impl std::fmt::Display for ServiceCode {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        self.serialize(fmt)
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceMode {
+    /// Only run locally, the result is committed
+    LocalOnly,
+    /// Run locally if possible, otherwise on the server
+    TryLocal,
+    ServerOnly,
+    /// Run in the build environment for every build
+    BuildTime,
+    /// Only run on explicit request
+    Manual,
+    /// Old name of `Manual`
+    Disabled,
+}
+
+impl ServiceMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServiceMode::LocalOnly => "localonly",
+            ServiceMode::TryLocal => "trylocal",
+            ServiceMode::ServerOnly => "serveronly",
+            ServiceMode::BuildTime => "buildtime",
+            ServiceMode::Manual => "manual",
+            ServiceMode::Disabled => "disabled",
+        }
+    }
+}
+
+// Serialized by hand as quick-xml would otherwise turn it into an element
+impl Serialize for ServiceMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl std::fmt::Display for ServiceMode {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        fmt.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub struct ServiceParam {
+    pub name: String,
+    #[serde(rename = "$value", default)]
+    pub value: String,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub struct Service {
+    pub name: String,
+    /// Mode of the service, runs both locally and on the server if unset
+    pub mode: Option<ServiceMode>,
+    #[serde(rename = "param", default)]
+    pub params: Vec<ServiceParam>,
+}
+
+impl Service {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            mode: None,
+            params: Vec::new(),
+        }
+    }
+
+    pub fn mode(mut self, mode: ServiceMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn param(mut self, name: String, value: String) -> Self {
+        self.params.push(ServiceParam { name, value });
+        self
+    }
+
+    /// The value of the first parameter called `name`
+    pub fn get_param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.value.as_str())
+    }
+}
+
+/// Contents of a `_service` file
+#[derive(Clone, Deserialize, Serialize, Debug, Default, Eq, PartialEq)]
+#[serde(rename = "services")]
+pub struct Services {
+    #[serde(rename = "service", default)]
+    pub services: Vec<Service>,
+}
+
+impl Services {
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        Ok(quick_xml::de::from_str(xml)?)
+    }
+
+    pub fn to_xml(&self) -> Result<String> {
+        Ok(quick_xml::se::to_string(self)?)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ServiceInfo {
+    pub code: ServiceCode,
+    pub xsrcmd5: Option<String>,
+    pub lsrcmd5: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LinkedPackage {
+    pub project: String,
+    pub package: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SourceInfo {
+    pub package: String,
+    pub rev: Option<String>,
+    pub vrev: Option<String>,
+    pub srcmd5: Option<String>,
+    pub lsrcmd5: Option<String>,
+    pub verifymd5: Option<String>,
+    pub filename: Option<String>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub linked: Vec<LinkedPackage>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SourceInfoList {
+    #[serde(rename = "sourceinfo", default)]
+    pub sourceinfos: Vec<SourceInfo>,
+}
+
+impl SourceInfoList {
+    pub fn get(&self, package: &str) -> Option<&SourceInfo> {
+        self.sourceinfos.iter().find(|s| s.package == package)
+    }
+
+    /// Packages whose sources changed compared to an earlier listing,
+    /// including packages which didn't exist before
+    pub fn changed_since<'a>(&'a self, earlier: &SourceInfoList) -> Vec<&'a str> {
+        self.sourceinfos
+            .iter()
+            .filter(|s| match earlier.get(&s.package) {
+                Some(e) => e.verifymd5 != s.verifymd5 || e.srcmd5 != s.srcmd5,
+                None => true,
+            })
+            .map(|s| s.package.as_str())
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct DirectoryListEntry {
+    name: String,
+}
+
+/// Listing of plain names, e.g. all projects or all packages in a project
+#[derive(Deserialize, Debug)]
+struct DirectoryList {
+    #[serde(rename = "entry", default)]
+    entries: Vec<DirectoryListEntry>,
+}
+
+impl DirectoryList {
+    fn names(self) -> Vec<String> {
+        self.entries.into_iter().map(|e| e.name).collect()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Directory {
+    pub name: String,
+    pub rev: String,
+    pub vrev: String,
+    pub srcmd5: String,
+    pub linkinfo: Option<LinkInfo>,
+    pub serviceinfo: Option<ServiceInfo>,
+    #[serde(default, rename = "entry")]
+    pub entries: Vec<DirectoryEntry>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+#[serde(rename = "entry")]
+pub struct CommitEntry {
+    pub name: String,
+    pub md5: String,
+}
+
+/// List of files making up a new package revision
+#[derive(Clone, Serialize, Debug, Default)]
+#[serde(rename = "directory")]
+pub struct CommitFileList {
+    #[serde(rename = "entry")]
+    pub entries: Vec<CommitEntry>,
+}
+
+impl CommitFileList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_entry(&mut self, name: String, md5: String) {
+        self.entries.push(CommitEntry { name, md5 })
+    }
+
+    pub fn entry(mut self, name: String, md5: String) -> Self {
+        self.add_entry(name, md5);
+        self
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CommitOptions {
+    pub comment: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum CommitResult {
+    /// The commit succeeded, resulting in the given revision
+    Success(Box<Directory>),
+    /// The server is missing the content of these entries, they should be
+    /// uploaded before retrying the commit
+    MissingEntries(Vec<CommitEntry>),
+}
+
+#[derive(Deserialize, Debug)]
+struct CommitResponse {
+    error: Option<String>,
+    #[serde(default, rename = "entry")]
+    entries: Vec<CommitEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SourceDiffRevision {
+    pub project: String,
+    pub package: String,
+    pub rev: Option<String>,
+    pub srcmd5: Option<String>,
+}
+
+#[derive(Copy, Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffFileState {
+    Added,
+    Deleted,
+    Changed,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DiffFileEntry {
+    pub name: String,
+    pub md5: String,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiffHunk {
+    pub old_start: u64,
+    pub old_lines: u64,
+    pub new_start: u64,
+    pub new_lines: u64,
+    /// Lines of the hunk including their ' ', '+' or '-' prefix, and
+    /// `\ No newline at end of file` markers
+    pub lines: Vec<String>,
+}
+
+/// Parse a `@@ -1,2 +1,3 @@` hunk header into (old_start, old_lines, new_start, new_lines)
+fn parse_hunk_header(line: &str) -> Option<(u64, u64, u64, u64)> {
+    fn range(r: &str) -> Option<(u64, u64)> {
+        match r.split_once(',') {
+            Some((start, lines)) => Some((start.parse().ok()?, lines.parse().ok()?)),
+            None => Some((r.parse().ok()?, 1)),
+        }
+    }
+
+    let mut parts = line.strip_prefix("@@ ")?.split(' ');
+    let (old_start, old_lines) = range(parts.next()?.strip_prefix('-')?)?;
+    let (new_start, new_lines) = range(parts.next()?.strip_prefix('+')?)?;
+    Some((old_start, old_lines, new_start, new_lines))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FileDiff {
+    pub lines: u64,
+    /// Set if the diff was cut off because of its size
+    pub shown: Option<u64>,
+    #[serde(rename = "$value", default)]
+    pub text: String,
+}
+
+impl FileDiff {
+    pub fn hunks(&self) -> Vec<DiffHunk> {
+        let mut hunks: Vec<DiffHunk> = Vec::new();
+        for line in self.text.lines() {
+            if let Some((old_start, old_lines, new_start, new_lines)) = parse_hunk_header(line) {
+                hunks.push(DiffHunk {
+                    old_start,
+                    old_lines,
+                    new_start,
+                    new_lines,
+                    lines: Vec::new(),
+                });
+            } else if let Some(hunk) = hunks.last_mut() {
+                hunk.lines.push(line.to_owned());
+            }
+        }
+        hunks
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SourceDiffFile {
+    pub state: DiffFileState,
+    pub old: Option<DiffFileEntry>,
+    pub new: Option<DiffFileEntry>,
+    pub diff: Option<FileDiff>,
+}
+
+fn deserialize_diff_files<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Vec<SourceDiffFile>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Files {
+        #[serde(rename = "file", default)]
+        files: Vec<SourceDiffFile>,
+    }
+
+    Ok(Files::deserialize(deserializer)?.files)
+}
+
+#[derive(Copy, Clone, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffIssueState {
+    Added,
+    Deleted,
+    Changed,
+    Kept,
+}
+
+/// Issue tracker reference found in the changes files of a diff
+#[derive(Deserialize, Debug)]
+pub struct DiffIssue {
+    pub state: DiffIssueState,
+    pub tracker: String,
+    pub name: String,
+    pub label: Option<String>,
+    pub url: Option<String>,
+}
+
+fn deserialize_diff_issues<'de, D>(deserializer: D) -> std::result::Result<Vec<DiffIssue>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Issues {
+        #[serde(rename = "issue", default)]
+        issues: Vec<DiffIssue>,
+    }
+
+    Ok(Issues::deserialize(deserializer)?.issues)
+}
+
+/// Structured result of a source diff (`view=xml`)
+#[derive(Deserialize, Debug)]
+pub struct SourceDiff {
+    pub key: String,
+    pub old: SourceDiffRevision,
+    pub new: SourceDiffRevision,
+    #[serde(default, deserialize_with = "deserialize_diff_files")]
+    pub files: Vec<SourceDiffFile>,
+    /// Only included when requested with `withissues`
+    #[serde(default, deserialize_with = "deserialize_diff_issues")]
+    pub issues: Vec<DiffIssue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ResultListResult {
+    pub project: String,
+    pub repository: String,
+    pub arch: String,
+    pub code: RepositoryCode,
+    #[serde(default)]
+    pub dirty: bool,
+    #[serde(rename = "status", default)]
+    pub statusses: Vec<BuildStatus>,
+    /// Binaries per package, only included with `ResultView::BinaryList`
+    #[serde(rename = "binarylist", default)]
+    pub binarylists: Vec<BinaryList>,
+    /// Only included with `ResultView::Summary`
+    pub summary: Option<ResultSummary>,
+}
+
+impl ResultListResult {
+    pub fn get_status(&self, package: &str) -> Option<&BuildStatus> {
+        self.statusses.iter().find(|s| s.package == package)
+    }
+
+    /// Statuses of a package and all its multibuild flavors
+    pub fn get_package_statuses<'a>(
+        &'a self,
+        package: &'a str,
+    ) -> impl Iterator<Item = &'a BuildStatus> + 'a {
+        self.statusses
+            .iter()
+            .filter(move |s| s.base_package() == package)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ResultList {
+    pub state: String,
+    #[serde(rename = "result", default)]
+    pub results: Vec<ResultListResult>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StatusCount {
+    pub code: PackageCode,
+    pub count: u64,
+}
+
+/// Number of packages per build state
+#[derive(Deserialize, Debug, Default)]
+pub struct ResultSummary {
+    #[serde(rename = "statuscount", default)]
+    pub statuscounts: Vec<StatusCount>,
+}
+
+impl ResultSummary {
+    pub fn count(&self, code: PackageCode) -> u64 {
+        self.statuscounts
+            .iter()
+            .filter(|c| c.code == code)
+            .map(|c| c.count)
+            .sum()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.statuscounts.iter().map(|c| c.count).sum()
+    }
+
+    fn add(&mut self, other: &ResultSummary) {
+        for c in &other.statuscounts {
+            match self.statuscounts.iter_mut().find(|s| s.code == c.code) {
+                Some(s) => s.count += c.count,
+                None => self.statuscounts.push(StatusCount {
+                    code: c.code,
+                    count: c.count,
+                }),
+            }
+        }
+    }
+}
+
+/// Package counts of a single repository and architecture
+#[derive(Debug)]
+pub struct RepositorySummary {
+    pub repository: String,
+    pub arch: String,
+    pub code: RepositoryCode,
+    pub dirty: bool,
+    pub summary: ResultSummary,
+}
+
+/// Package counts per build state of a whole project
+#[derive(Debug)]
+pub struct ProjectSummary {
+    pub repositories: Vec<RepositorySummary>,
+}
+
+impl ProjectSummary {
+    pub fn get(&self, repository: &str, arch: &str) -> Option<&RepositorySummary> {
+        self.repositories
+            .iter()
+            .find(|r| r.repository == repository && r.arch == arch)
+    }
+
+    /// Summary of all repositories and architectures combined
+    pub fn total_summary(&self) -> ResultSummary {
+        let mut total = ResultSummary::default();
+        for r in &self.repositories {
+            total.add(&r.summary);
+        }
+        total
+    }
+}
+
+impl From<ResultList> for ProjectSummary {
+    fn from(list: ResultList) -> Self {
+        let repositories = list
+            .results
+            .into_iter()
+            .map(|r| RepositorySummary {
+                repository: r.repository,
+                arch: r.arch,
+                code: r.code,
+                dirty: r.dirty,
+                summary: r.summary.unwrap_or_default(),
+            })
+            .collect();
+        ProjectSummary { repositories }
+    }
+}
+
+/// Contents of a `_multibuild` file
+#[derive(Clone, Deserialize, Serialize, Debug, Default, Eq, PartialEq)]
+#[serde(rename = "multibuild")]
+pub struct Multibuild {
+    #[serde(rename = "flavor", default, serialize_with = "serialize_elements")]
+    pub flavors: Vec<String>,
+    /// Flavors in the older `<package>` syntax
+    #[serde(rename = "package", default, serialize_with = "serialize_elements")]
+    pub packages: Vec<String>,
+}
+
+impl Multibuild {
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        Ok(quick_xml::de::from_str(xml)?)
+    }
+
+    pub fn to_xml(&self) -> Result<String> {
+        Ok(quick_xml::se::to_string(self)?)
+    }
+
+    /// All flavors regardless of the syntax used to define them
+    pub fn all_flavors(&self) -> impl Iterator<Item = &str> {
+        self.flavors
+            .iter()
+            .chain(self.packages.iter())
+            .map(|f| f.as_str())
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Flag {
+    Enable {
+        repository: Option<String>,
+        arch: Option<String>,
+    },
+    Disable {
+        repository: Option<String>,
+        arch: Option<String>,
+    },
+}
+
+/// Enable/disable switches like `<build>` or `<publish>`, the most specific
+/// matching flag wins
+#[derive(Clone, Deserialize, Serialize, Debug, Default, Eq, PartialEq)]
+pub struct Flags {
+    #[serde(rename = "$value", default)]
+    pub flags: Vec<Flag>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub struct PersonRole {
+    pub userid: String,
+    pub role: String,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub struct GroupRole {
+    pub groupid: String,
+    pub role: String,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub struct RepositoryPath {
+    pub project: String,
+    pub repository: String,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub struct ReleaseTarget {
+    pub project: String,
+    pub repository: String,
+    pub trigger: Option<String>,
+}
+
+const REPOSITORY_META_ATTRIBUTES: &[&str] = &["name", "rebuild", "block", "linkedbuild"];
+
+/// Elements covered by `RepositoryMeta`, in schema order
+const REPOSITORY_META_ELEMENTS: &[&str] = &["releasetarget", "path", "arch"];
+
+/// Repository of a project
+///
+/// Other content like `<download>` or `<hostsystem>` is kept as long as the
+/// repository is part of a `ProjectMeta` read with `from_xml`.
+#[derive(Clone, Deserialize, Serialize, Debug, Default, Eq, PartialEq)]
+pub struct RepositoryMeta {
+    pub name: String,
+    pub rebuild: Option<String>,
+    pub block: Option<String>,
+    pub linkedbuild: Option<String>,
+    #[serde(rename = "releasetarget", default)]
+    pub release_targets: Vec<ReleaseTarget>,
+    #[serde(rename = "path", default)]
+    pub paths: Vec<RepositoryPath>,
+    #[serde(rename = "arch", default, serialize_with = "serialize_elements")]
+    pub arches: Vec<String>,
+    #[serde(skip)]
+    unknown: UnknownContent,
+}
+
+impl RepositoryMeta {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectKind {
+    Standard,
+    Maintenance,
+    MaintenanceIncident,
+    MaintenanceRelease,
+}
+
+impl ProjectKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectKind::Standard => "standard",
+            ProjectKind::Maintenance => "maintenance",
+            ProjectKind::MaintenanceIncident => "maintenance_incident",
+            ProjectKind::MaintenanceRelease => "maintenance_release",
+        }
+    }
+}
+
+impl Serialize for ProjectKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+const PROJECT_META_ATTRIBUTES: &[&str] = &["name", "kind"];
+
+/// Elements covered by `ProjectMeta`, in schema order
+const PROJECT_META_ELEMENTS: &[&str] = &[
+    "title",
+    "description",
+    "url",
+    "scmsync",
+    "person",
+    "group",
+    "lock",
+    "build",
+    "publish",
+    "useforbuild",
+    "debuginfo",
+    "repository",
+];
+
+/// Project configuration as stored in `/source/<project>/_meta`
+///
+/// Attributes and elements not covered by this model are kept when the meta
+/// was created with `from_xml` and written back out by `to_xml`.
+#[derive(Clone, Deserialize, Serialize, Debug, Default, Eq, PartialEq)]
+#[serde(rename = "project")]
+pub struct ProjectMeta {
+    pub name: String,
+    /// Standard project if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ProjectKind>,
+    #[serde(default, serialize_with = "serialize_element")]
+    pub title: String,
+    #[serde(default, serialize_with = "serialize_element")]
+    pub description: String,
+    #[serde(
+        serialize_with = "serialize_element",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub url: Option<String>,
+    #[serde(
+        serialize_with = "serialize_element",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub scmsync: Option<String>,
+    #[serde(rename = "person", default)]
+    pub persons: Vec<PersonRole>,
+    #[serde(rename = "group", default)]
+    pub groups: Vec<GroupRole>,
+    pub lock: Option<Flags>,
+    pub build: Option<Flags>,
+    pub publish: Option<Flags>,
+    pub useforbuild: Option<Flags>,
+    pub debuginfo: Option<Flags>,
+    #[serde(rename = "repository", default)]
+    pub repositories: Vec<RepositoryMeta>,
+    #[serde(skip)]
+    unknown: UnknownContent,
+}
+
+impl ProjectMeta {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        let mut meta: Self = quick_xml::de::from_str(xml)?;
+        meta.unknown =
+            UnknownContent::from_xml(xml, PROJECT_META_ATTRIBUTES, PROJECT_META_ELEMENTS)?;
+        let ranges = child_element_ranges(xml, "repository")?;
+        for (repository, range) in meta.repositories.iter_mut().zip(ranges) {
+            repository.unknown = UnknownContent::from_xml(
+                &xml[range],
+                REPOSITORY_META_ATTRIBUTES,
+                REPOSITORY_META_ELEMENTS,
+            )?;
+        }
+        Ok(meta)
+    }
+
+    pub fn to_xml(&self) -> Result<String> {
+        let mut xml = quick_xml::se::to_string(self)?;
+        let ranges = child_element_ranges(&xml, "repository")?;
+        for (repository, range) in self.repositories.iter().zip(ranges).rev() {
+            let element = repository.unknown.insert(
+                xml[range.clone()].to_owned(),
+                "repository",
+                REPOSITORY_META_ELEMENTS,
+            )?;
+            xml.replace_range(range, &element);
+        }
+        Ok(self.unknown.insert(xml, "project", PROJECT_META_ELEMENTS)?)
+    }
+
+    pub fn repository(&self, name: &str) -> Option<&RepositoryMeta> {
+        self.repositories.iter().find(|r| r.name == name)
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Eq, PartialEq)]
+pub struct DevelPackage {
+    pub project: String,
+    pub package: Option<String>,
+}
+
+const PACKAGE_META_ATTRIBUTES: &[&str] = &["name", "project"];
+
+/// Elements covered by `PackageMeta`, in schema order
+const PACKAGE_META_ELEMENTS: &[&str] = &[
+    "title",
+    "description",
+    "devel",
+    "person",
+    "group",
+    "lock",
+    "build",
+    "publish",
+    "useforbuild",
+    "debuginfo",
+    "url",
+    "scmsync",
+];
+
+/// Package configuration as stored in `/source/<project>/<package>/_meta`
+///
+/// Attributes and elements not covered by this model are kept when the meta
+/// was created with `from_xml` and written back out by `to_xml`.
+#[derive(Clone, Deserialize, Serialize, Debug, Default, Eq, PartialEq)]
+#[serde(rename = "package")]
+pub struct PackageMeta {
+    pub name: String,
+    pub project: String,
+    #[serde(default, serialize_with = "serialize_element")]
+    pub title: String,
+    #[serde(default, serialize_with = "serialize_element")]
+    pub description: String,
+    pub devel: Option<DevelPackage>,
+    #[serde(rename = "person", default)]
+    pub persons: Vec<PersonRole>,
+    #[serde(rename = "group", default)]
+    pub groups: Vec<GroupRole>,
+    pub lock: Option<Flags>,
+    pub build: Option<Flags>,
+    pub publish: Option<Flags>,
+    pub useforbuild: Option<Flags>,
+    pub debuginfo: Option<Flags>,
+    #[serde(
+        serialize_with = "serialize_element",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub url: Option<String>,
+    #[serde(
+        serialize_with = "serialize_element",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub scmsync: Option<String>,
+    #[serde(skip)]
+    unknown: UnknownContent,
+}
+
+impl PackageMeta {
+    pub fn new(project: String, name: String) -> Self {
+        Self {
+            name,
+            project,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        let mut meta: Self = quick_xml::de::from_str(xml)?;
+        meta.unknown =
+            UnknownContent::from_xml(xml, PACKAGE_META_ATTRIBUTES, PACKAGE_META_ELEMENTS)?;
+        Ok(meta)
+    }
+
+    pub fn to_xml(&self) -> Result<String> {
+        let xml = quick_xml::se::to_string(self)?;
+        Ok(self.unknown.insert(xml, "package", PACKAGE_META_ELEMENTS)?)
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestState {
+    New,
+    Review,
+    Accepted,
+    Declined,
+    Revoked,
+    Superseded,
+    Deleted,
+}
+
+impl RequestState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestState::New => "new",
+            RequestState::Review => "review",
+            RequestState::Accepted => "accepted",
+            RequestState::Declined => "declined",
+            RequestState::Revoked => "revoked",
+            RequestState::Superseded => "superseded",
+            RequestState::Deleted => "deleted",
+        }
+    }
+
+    /// Whether the request can't be changed anymore
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            Self::Accepted | Self::Revoked | Self::Superseded | Self::Deleted
+        )
+    }
+}
+
+impl std::fmt::Display for RequestState {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        fmt.write_str(self.as_str())
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewState {
+    New,
+    Accepted,
+    Declined,
+    Superseded,
+    Obsoleted,
+}
+
+#[derive(Copy, Clone, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestActionType {
+    Submit,
+    Delete,
+    AddRole,
+    SetBugowner,
+    ChangeDevel,
+    MaintenanceIncident,
+    MaintenanceRelease,
+    Release,
+}
+
+impl RequestActionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestActionType::Submit => "submit",
+            RequestActionType::Delete => "delete",
+            RequestActionType::AddRole => "add_role",
+            RequestActionType::SetBugowner => "set_bugowner",
+            RequestActionType::ChangeDevel => "change_devel",
+            RequestActionType::MaintenanceIncident => "maintenance_incident",
+            RequestActionType::MaintenanceRelease => "maintenance_release",
+            RequestActionType::Release => "release",
+        }
+    }
+}
+
+impl Serialize for RequestActionType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// What happens to the source package once a submit request is accepted
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SourceUpdate {
+    Update,
+    NoUpdate,
+    Cleanup,
+}
+
+impl SourceUpdate {
+    const VARIANTS: &'static [&'static str] = &["update", "noupdate", "cleanup"];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceUpdate::Update => "update",
+            SourceUpdate::NoUpdate => "noupdate",
+            SourceUpdate::Cleanup => "cleanup",
+        }
+    }
+}
+
+// Sent as element text, which the derived implementation would take as the
+// variant element name
+impl<'de> Deserialize<'de> for SourceUpdate {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        match s.as_str() {
+            "update" => Ok(SourceUpdate::Update),
+            "noupdate" => Ok(SourceUpdate::NoUpdate),
+            "cleanup" => Ok(SourceUpdate::Cleanup),
+            _ => Err(serde::de::Error::unknown_variant(&s, Self::VARIANTS)),
+        }
+    }
+}
+
+impl Serialize for SourceUpdate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct RequestSource {
+    pub project: String,
+    pub package: Option<String>,
+    pub rev: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct RequestTarget {
+    pub project: String,
+    pub package: Option<String>,
+    pub releaseproject: Option<String>,
+    pub repository: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct RequestRole {
+    pub name: String,
+    pub role: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, Default)]
+pub struct RequestActionOptions {
+    #[serde(
+        serialize_with = "serialize_element",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sourceupdate: Option<SourceUpdate>,
+    /// Update the link in the source package to the new target revision
+    #[serde(
+        serialize_with = "serialize_element",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updatelink: Option<bool>,
+    /// Create a devel package relation from the target to the source
+    #[serde(
+        serialize_with = "serialize_element",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub makeoriginolder: Option<bool>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename = "action")]
+pub struct RequestAction {
+    #[serde(rename = "type")]
+    pub action_type: RequestActionType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<RequestSource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<RequestTarget>,
+    /// User for `add_role` and `set_bugowner` actions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub person: Option<RequestRole>,
+    /// Group for `add_role` and `set_bugowner` actions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<RequestRole>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<RequestActionOptions>,
+}
+
+impl RequestAction {
+    fn new(
+        action_type: RequestActionType,
+        source: Option<RequestSource>,
+        target: Option<RequestTarget>,
+    ) -> Self {
+        RequestAction {
+            action_type,
+            source,
+            target,
+            person: None,
+            group: None,
+            options: None,
+        }
+    }
+
+    fn source(project: String, package: Option<String>) -> Option<RequestSource> {
+        Some(RequestSource {
+            project,
+            package,
+            rev: None,
+        })
+    }
+
+    fn target(project: String, package: Option<String>) -> Option<RequestTarget> {
+        Some(RequestTarget {
+            project,
+            package,
+            releaseproject: None,
+            repository: None,
+        })
+    }
+
+    /// Submit a package to another project, under the same name unless
+    /// changed with `target_package`
+    pub fn submit(source_project: String, source_package: String, target_project: String) -> Self {
+        Self::new(
+            RequestActionType::Submit,
+            Self::source(source_project, Some(source_package)),
+            Self::target(target_project, None),
+        )
+    }
+
+    /// Delete a package, or the whole project if no package is given
+    pub fn delete(project: String, package: Option<String>) -> Self {
+        Self::new(
+            RequestActionType::Delete,
+            None,
+            Self::target(project, package),
+        )
+    }
+
+    /// Add a role in a project or package, see `person` and `group`
+    pub fn add_role(project: String, package: Option<String>) -> Self {
+        Self::new(
+            RequestActionType::AddRole,
+            None,
+            Self::target(project, package),
+        )
+    }
+
+    /// Make the given devel package the development origin of a package
+    pub fn change_devel(
+        devel_project: String,
+        devel_package: String,
+        project: String,
+        package: String,
+    ) -> Self {
+        Self::new(
+            RequestActionType::ChangeDevel,
+            Self::source(devel_project, Some(devel_package)),
+            Self::target(project, Some(package)),
+        )
+    }
+
+    /// Add a package, or a whole project, to a maintenance incident
+    pub fn maintenance_incident(
+        source_project: String,
+        source_package: Option<String>,
+        target_project: String,
+    ) -> Self {
+        Self::new(
+            RequestActionType::MaintenanceIncident,
+            Self::source(source_project, source_package),
+            Self::target(target_project, None),
+        )
+    }
+
+    pub fn maintenance_release(
+        source_project: String,
+        source_package: String,
+        target_project: String,
+    ) -> Self {
+        Self::new(
+            RequestActionType::MaintenanceRelease,
+            Self::source(source_project, Some(source_package)),
+            Self::target(target_project, None),
+        )
+    }
+
+    /// Release the build results of a package, or of a whole project, to the
+    /// release targets of its repositories
+    pub fn release(source_project: String, source_package: Option<String>) -> Self {
+        Self::new(
+            RequestActionType::Release,
+            Self::source(source_project, source_package),
+            None,
+        )
+    }
+
+    pub fn target_package(mut self, package: String) -> Self {
+        if let Some(target) = &mut self.target {
+            target.package = Some(package);
+        }
+        self
+    }
+
+    /// Release into this project rather than the one of the repository
+    pub fn releaseproject(mut self, project: String) -> Self {
+        if let Some(target) = &mut self.target {
+            target.releaseproject = Some(project);
+        }
+        self
+    }
+
+    /// Source revision to use, the current one when the request is created
+    /// if unset
+    pub fn rev(mut self, rev: String) -> Self {
+        if let Some(source) = &mut self.source {
+            source.rev = Some(rev);
+        }
+        self
+    }
+
+    pub fn person(mut self, name: String, role: String) -> Self {
+        self.person = Some(RequestRole {
+            name,
+            role: Some(role),
+        });
+        self
+    }
+
+    pub fn group(mut self, name: String, role: String) -> Self {
+        self.group = Some(RequestRole {
+            name,
+            role: Some(role),
+        });
+        self
+    }
+
+    pub fn sourceupdate(mut self, sourceupdate: SourceUpdate) -> Self {
+        self.options
+            .get_or_insert_with(Default::default)
+            .sourceupdate = Some(sourceupdate);
+        self
+    }
+
+    pub fn updatelink(mut self, updatelink: bool) -> Self {
+        self.options.get_or_insert_with(Default::default).updatelink = Some(updatelink);
+        self
+    }
+
+    pub fn makeoriginolder(mut self, makeoriginolder: bool) -> Self {
+        self.options
+            .get_or_insert_with(Default::default)
+            .makeoriginolder = Some(makeoriginolder);
+        self
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RequestStatus {
+    pub name: RequestState,
+    pub who: String,
+    pub when: NaiveDateTime,
+    pub created: Option<NaiveDateTime>,
+    pub superseded_by: Option<u64>,
+    pub comment: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RequestHistory {
+    pub who: String,
+    pub when: NaiveDateTime,
+    pub description: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// Whom a review is assigned to
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Reviewer {
+    User(String),
+    Group(String),
+    Project(String),
+    /// Maintainers of a package, as project and package name
+    Package(String, String),
+}
+
+impl Reviewer {
+    fn append_query(&self, u: &mut Url) {
+        match self {
+            Reviewer::User(user) => {
+                u.query_pairs_mut().append_pair("by_user", user);
+            }
+            Reviewer::Group(group) => {
+                u.query_pairs_mut().append_pair("by_group", group);
+            }
+            Reviewer::Project(project) => {
+                u.query_pairs_mut().append_pair("by_project", project);
+            }
+            Reviewer::Package(project, package) => {
+                u.query_pairs_mut()
+                    .append_pair("by_project", project)
+                    .append_pair("by_package", package);
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Review {
+    pub state: ReviewState,
+    pub by_user: Option<String>,
+    pub by_group: Option<String>,
+    pub by_project: Option<String>,
+    pub by_package: Option<String>,
+    /// Who last changed the review
+    pub who: Option<String>,
+    pub when: Option<NaiveDateTime>,
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub history: Vec<RequestHistory>,
+}
+
+impl Review {
+    pub fn reviewer(&self) -> Option<Reviewer> {
+        if let Some(user) = &self.by_user {
+            Some(Reviewer::User(user.clone()))
+        } else if let Some(group) = &self.by_group {
+            Some(Reviewer::Group(group.clone()))
+        } else if let Some(project) = &self.by_project {
+            match &self.by_package {
+                Some(package) => Some(Reviewer::Package(project.clone(), package.clone())),
+                None => Some(Reviewer::Project(project.clone())),
+            }
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Request {
+    pub id: u64,
+    pub creator: Option<String>,
+    #[serde(rename = "action", default)]
+    pub actions: Vec<RequestAction>,
+    pub state: RequestStatus,
+    #[serde(rename = "review", default)]
+    pub reviews: Vec<Review>,
+    #[serde(default)]
+    pub history: Vec<RequestHistory>,
+    pub description: Option<String>,
+}
+
+/// A request to be created on the server
+#[derive(Clone, Serialize, Debug, Default)]
+#[serde(rename = "request")]
+pub struct NewRequest {
+    #[serde(rename = "action")]
+    pub actions: Vec<RequestAction>,
+    #[serde(
+        serialize_with = "serialize_element",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub description: Option<String>,
+}
+
+impl NewRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_action(&mut self, action: RequestAction) {
+        self.actions.push(action)
+    }
+
+    pub fn action(mut self, action: RequestAction) -> Self {
+        self.add_action(action);
+        self
+    }
+
+    pub fn description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RequestDiffAction {
+    #[serde(rename = "type")]
+    pub action_type: RequestActionType,
+    pub source: Option<RequestSource>,
+    pub target: Option<RequestTarget>,
+    /// Diffs of the package and of the packages linking to it
+    #[serde(rename = "sourcediff", default)]
+    pub sourcediffs: Vec<SourceDiff>,
+}
+
+/// Structured diff of all actions of a request
+#[derive(Deserialize, Debug)]
+pub struct RequestDiff {
+    pub id: u64,
+    #[serde(rename = "action", default)]
+    pub actions: Vec<RequestDiffAction>,
+}
+
+impl RequestDiff {
+    /// Issues referenced by any of the actions
+    pub fn issues(&self) -> impl Iterator<Item = &DiffIssue> {
+        self.actions
+            .iter()
+            .flat_map(|a| a.sourcediffs.iter())
+            .flat_map(|d| d.issues.iter())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct RequestCollection {
+    #[serde(rename = "request", default)]
+    requests: Vec<Request>,
+}
+
+fn deserialize_comment_time<'de, D>(deserializer: D) -> std::result::Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S UTC")
+        .map(|t| DateTime::from_naive_utc_and_offset(t, Utc))
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Comment {
+    pub id: u64,
+    pub who: String,
+    #[serde(deserialize_with = "deserialize_comment_time")]
+    pub when: DateTime<Utc>,
+    /// The comment this one replies to
+    pub parent: Option<u64>,
+    #[serde(rename = "$value", default)]
+    pub text: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommentList {
+    #[serde(rename = "comment", default)]
+    pub comments: Vec<Comment>,
+}
+
+impl CommentList {
+    pub fn get(&self, id: u64) -> Option<&Comment> {
+        self.comments.iter().find(|c| c.id == id)
+    }
+
+    /// Comments starting a thread, i.e. not replying to another comment
+    pub fn top_level(&self) -> impl Iterator<Item = &Comment> {
+        self.comments.iter().filter(|c| c.parent.is_none())
+    }
+
+    /// Direct replies to the given comment
+    pub fn replies(&self, id: u64) -> impl Iterator<Item = &Comment> {
+        self.comments.iter().filter(move |c| c.parent == Some(id))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct LogEntryEntry {
+    size: usize,
+    mtime: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct LogEntry {
+    #[serde(rename = "entry")]
+    pub entries: Vec<LogEntryEntry>,
+}
+
+enum PackageLogRequest {
+    Initial,
+    Request(BoxFuture<'static, Result<Response>>),
+    Stream((BoxStream<'static, reqwest::Result<Bytes>>, bool)),
+}
+
+pub struct PackageLogStream<'a> {
+    client: &'a Client,
+    url: Url,
+    offset: usize,
+    request: PackageLogRequest,
+}
+
+impl<'a> PackageLogStream<'a> {
+    fn new(client: &'a Client, offset: usize, url: Url) -> Self {
+        Self {
+            client,
+            url,
+            offset,
+            request: PackageLogRequest::Initial,
+        }
+    }
+
+    fn request_log(&self, offset: usize) -> Result<Url> {
+        let mut url = self.url.clone();
+        url.query_pairs_mut()
+            .append_pair("nostream", "1")
+            .append_pair("start", &format!("{}", offset));
+        Ok(url)
+    }
+
+}
+
+impl Stream for PackageLogStream<'_> {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.get_mut();
+
+        loop {
+            match me.request {
                 PackageLogRequest::Initial => {
                     let u = match me.request_log(me.offset) {
                         Ok(u) => u,
@@ -274,138 +2383,1505 @@ impl Stream for PackageLogStream<'_> {
     }
 }
 
-pub struct PackageLog<'a> {
+/// Number of consecutive retries when watching results before giving up
+const WATCH_RETRIES: u32 = 5;
+
+/// Errors that may go away on retry, like timeouts or server side failures
+fn is_transient(e: &Error) -> bool {
+    match e {
+        Error::RequestError(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.is_body()
+                || e.status().is_some_and(|s| s.is_server_error())
+        }
+        _ => false,
+    }
+}
+
+struct ResultWatch<'a> {
+    client: &'a Client,
+    url: Url,
+    oldstate: Option<String>,
+    done: bool,
+}
+
+impl ResultWatch<'_> {
+    async fn next(&mut self) -> Result<ResultList> {
+        let mut retries = 0;
+        loop {
+            let mut u = self.url.clone();
+            if let Some(oldstate) = &self.oldstate {
+                u.query_pairs_mut().append_pair("oldstate", oldstate);
+            }
+
+            match self.client.fetch::<ResultList>(u).await {
+                // The server gave up waiting without anything changing
+                Ok(r) if self.oldstate.as_ref() == Some(&r.state) => retries = 0,
+                Ok(r) => {
+                    self.oldstate = Some(r.state.clone());
+                    return Ok(r);
+                }
+                Err(e) if is_transient(&e) && retries < WATCH_RETRIES => {
+                    tokio::time::sleep(Duration::from_secs(1 << retries)).await;
+                    retries += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Stream the current build results followed by every change to them
+fn watch_results(client: &Client, url: Url) -> BoxStream<'_, Result<ResultList>> {
+    let watch = ResultWatch {
+        client,
+        url,
+        oldstate: None,
+        done: false,
+    };
+
+    stream::unfold(watch, |mut watch| async move {
+        if watch.done {
+            return None;
+        }
+        let r = watch.next().await;
+        watch.done = r.is_err();
+        Some((r, watch))
+    })
+    .boxed()
+}
+
+enum DownloadRequest {
+    Request(BoxFuture<'static, Result<Response>>),
+    Stream(BoxStream<'static, reqwest::Result<Bytes>>),
+}
+
+/// Stream of the contents of a single file on the server
+pub struct DownloadStream {
+    request: DownloadRequest,
+}
+
+impl DownloadStream {
+    fn new(request: RequestBuilder) -> Self {
+        Self {
+            request: DownloadRequest::Request(Client::send_with_error(request).boxed()),
+        }
+    }
+}
+
+impl Stream for DownloadStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.get_mut();
+
+        loop {
+            match me.request {
+                DownloadRequest::Request(ref mut r) => match ready!(r.as_mut().poll(cx)) {
+                    Ok(r) => me.request = DownloadRequest::Stream(r.bytes_stream().boxed()),
+                    Err(e) => return Poll::Ready(Some(Err(e))),
+                },
+                DownloadRequest::Stream(ref mut stream) => {
+                    return match ready!(stream.as_mut().poll_next(cx)) {
+                        Some(Ok(b)) => Poll::Ready(Some(Ok(b))),
+                        Some(Err(e)) => Poll::Ready(Some(Err(e.into()))),
+                        None => Poll::Ready(None),
+                    };
+                }
+            }
+        }
+    }
+}
+
+pub struct PackageLog<'a> {
+    client: &'a Client,
+    project: String,
+    package: String,
+    repository: String,
+    arch: String,
+}
+
+impl PackageLog<'_> {
+    fn request(&self) -> Result<Url> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("build")
+            .push(&self.project)
+            .push(&self.repository)
+            .push(&self.arch)
+            .push(&self.package)
+            .push("_log");
+        Ok(u)
+    }
+
+    pub fn stream(&self, offset: usize) -> Result<PackageLogStream<'_>> {
+        let u = self.request()?;
+        println!("Requesting: {:?}", u);
+        Ok(PackageLogStream::new(self.client, offset, u))
+    }
+
+    /// Returns size and mtime
+    pub async fn entry(&self) -> Result<(usize, u64)> {
+        let mut u = self.request()?;
+        u.query_pairs_mut().append_pair("view", "entry");
+
+        let e: LogEntry = self.client.fetch(u).await?;
+        if let Some(entry) = e.entries.first() {
+            Ok((entry.size, entry.mtime))
+        } else {
+            Err(Error::UnexpectedResult)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FileOptions {
+    /// Revision to fetch the file from, the latest revision if unset
+    pub rev: Option<String>,
+    /// Expand links, e.g. to get a file from the link target
+    pub expand: bool,
+}
+
+/// Options for listing the sources of a package
+///
+/// There is no `view` option: each view returns a different document, so
+/// views are available through their own methods instead, e.g.
+/// `PackageBuilder::source_info` for `view=info`.
+#[derive(Clone, Debug, Default)]
+pub struct ListOptions {
+    /// Revision to list, the latest revision if unset
+    pub rev: Option<String>,
+    /// Expand links, listing the merged sources instead of the link itself
+    pub expand: bool,
+    /// Revision of the link target to expand against, e.g. `base`
+    pub linkrev: Option<String>,
+    /// List the meta files rather than the sources
+    pub meta: bool,
+    /// List the sources of a deleted package
+    pub deleted: bool,
+}
+
+/// Options for a source diff, by default the current revision of the package
+/// is compared with its previous revision
+#[derive(Clone, Debug, Default)]
+pub struct DiffOptions {
+    pub rev: Option<String>,
+    /// Project to compare against, the package's own project if unset
+    pub oproject: Option<String>,
+    /// Package to compare against, the package itself if unset
+    pub opackage: Option<String>,
+    pub orev: Option<String>,
+    pub linkrev: Option<String>,
+    pub olinkrev: Option<String>,
+    pub expand: bool,
+    /// Produce a unified diff over all files (text diff only)
+    pub unified: bool,
+    /// Compare the meta files rather than the sources
+    pub meta: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DeleteOptions {
+    /// Delete even if other projects or packages depend on it
+    pub force: bool,
+    /// Also delete subprojects (projects only)
+    pub recursive: bool,
+    pub comment: Option<String>,
+}
+
+impl DeleteOptions {
+    fn append_query(&self, u: &mut Url) {
+        if self.force {
+            u.query_pairs_mut().append_pair("force", "1");
+        }
+        if self.recursive {
+            u.query_pairs_mut().append_pair("recursive_remove", "1");
+        }
+        if let Some(comment) = &self.comment {
+            u.query_pairs_mut().append_pair("comment", comment);
+        }
+    }
+}
+
+/// Selection of build jobs a build command applies to, everything if empty
+#[derive(Clone, Debug, Default)]
+pub struct BuildCommandOptions {
+    /// Packages to act on, in addition to the package itself when used from
+    /// a `PackageBuilder`
+    pub packages: Vec<String>,
+    pub repositories: Vec<String>,
+    pub arches: Vec<String>,
+    /// Only act on packages currently in one of these states
+    pub codes: Vec<PackageCode>,
+}
+
+impl BuildCommandOptions {
+    fn append_query(&self, u: &mut Url) {
+        for package in &self.packages {
+            u.query_pairs_mut().append_pair("package", package);
+        }
+        for repository in &self.repositories {
+            u.query_pairs_mut().append_pair("repository", repository);
+        }
+        for arch in &self.arches {
+            u.query_pairs_mut().append_pair("arch", arch);
+        }
+        for code in &self.codes {
+            u.query_pairs_mut().append_pair("code", &code.to_string());
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BuildDepInfoView {
+    /// Dependencies as package names rather than binary names
+    PkgNames,
+    /// Packages depending on each package
+    RevPkgNames,
+}
+
+impl BuildDepInfoView {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BuildDepInfoView::PkgNames => "pkgnames",
+            BuildDepInfoView::RevPkgNames => "revpkgnames",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BuildDepInfoOptions {
+    /// Only include these packages, all packages if empty
+    pub packages: Vec<String>,
+    pub view: Option<BuildDepInfoView>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ResultView {
+    Status,
+    BinaryList,
+    Summary,
+}
+
+impl ResultView {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ResultView::Status => "status",
+            ResultView::BinaryList => "binarylist",
+            ResultView::Summary => "summary",
+        }
+    }
+}
+
+/// Filters for build results, everything is included for empty filters
+#[derive(Clone, Debug, Default)]
+pub struct ResultOptions {
+    pub repositories: Vec<String>,
+    pub arches: Vec<String>,
+    /// Packages to include, in addition to the package itself when used from
+    /// a `PackageBuilder`
+    pub packages: Vec<String>,
+    /// Include the results of multibuild flavors
+    pub multibuild: bool,
+    /// Include packages linking to the selected packages within the project
+    pub locallink: bool,
+    /// Show the result of the last build rather than the current state
+    pub lastbuild: bool,
+    /// Parts to include, only `ResultView::Status` if empty
+    pub views: Vec<ResultView>,
+}
+
+impl ResultOptions {
+    fn append_query(&self, u: &mut Url) {
+        for repository in &self.repositories {
+            u.query_pairs_mut().append_pair("repository", repository);
+        }
+        for arch in &self.arches {
+            u.query_pairs_mut().append_pair("arch", arch);
+        }
+        for package in &self.packages {
+            u.query_pairs_mut().append_pair("package", package);
+        }
+        if self.multibuild {
+            u.query_pairs_mut().append_pair("multibuild", "1");
+        }
+        if self.locallink {
+            u.query_pairs_mut().append_pair("locallink", "1");
+        }
+        if self.lastbuild {
+            u.query_pairs_mut().append_pair("lastbuild", "1");
+        }
+        for view in &self.views {
+            u.query_pairs_mut().append_pair("view", view.as_str());
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct JobHistoryOptions {
+    /// Packages to include, in addition to the package itself when used from
+    /// a `PackageBuilder`
+    pub packages: Vec<String>,
+    /// Codes to include; `JobHistoryCode::Unknown` only stands for codes this
+    /// crate doesn't know and is ignored
+    pub codes: Vec<JobHistoryCode>,
+    /// Maximum number of entries to return, the newest ones are kept
+    pub limit: Option<u32>,
+}
+
+impl JobHistoryOptions {
+    fn append_query(&self, u: &mut Url) {
+        for package in &self.packages {
+            u.query_pairs_mut().append_pair("package", package);
+        }
+        for code in &self.codes {
+            if *code != JobHistoryCode::Unknown {
+                u.query_pairs_mut().append_pair("code", &code.to_string());
+            }
+        }
+        if let Some(limit) = self.limit {
+            u.query_pairs_mut()
+                .append_pair("limit", &format!("{}", limit));
+        }
+    }
+}
+
+/// Filters for listing requests, at least one of them has to be set
+#[derive(Clone, Debug, Default)]
+pub struct RequestListOptions {
+    /// Requests created by or involving this user
+    pub user: Option<String>,
+    pub project: Option<String>,
+    /// Only used together with `project`
+    pub package: Option<String>,
+    /// Requests in any of these states, all open requests if empty
+    pub states: Vec<RequestState>,
+    /// Requests with actions of any of these types
+    pub types: Vec<RequestActionType>,
+}
+
+impl RequestListOptions {
+    fn append_query(&self, u: &mut Url) {
+        if let Some(user) = &self.user {
+            u.query_pairs_mut().append_pair("user", user);
+        }
+        if let Some(project) = &self.project {
+            u.query_pairs_mut().append_pair("project", project);
+        }
+        if let Some(package) = &self.package {
+            u.query_pairs_mut().append_pair("package", package);
+        }
+        if !self.states.is_empty() {
+            let states: Vec<_> = self.states.iter().map(|s| s.as_str()).collect();
+            u.query_pairs_mut().append_pair("states", &states.join(","));
+        }
+        if !self.types.is_empty() {
+            let types: Vec<_> = self.types.iter().map(|t| t.as_str()).collect();
+            u.query_pairs_mut().append_pair("types", &types.join(","));
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RequestDiffOptions {
+    /// Include the issues referenced in changes files (structured diff only)
+    pub withissues: bool,
+    /// Compare against the given superseded request rather than the target
+    pub superseded: Option<u64>,
+}
+
+impl RequestDiffOptions {
+    fn append_query(&self, u: &mut Url) {
+        if self.withissues {
+            u.query_pairs_mut().append_pair("withissues", "1");
+        }
+        if let Some(superseded) = self.superseded {
+            u.query_pairs_mut()
+                .append_pair("diff_to_superseded", &superseded.to_string());
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BranchOptions {
+    /// Project to branch into, `home:<user>:branches:<project>` if unset
+    pub target_project: Option<String>,
+    pub target_package: Option<String>,
+    /// Set the repositories of the target project up based on the origin
+    pub add_repositories: bool,
+    pub add_repositories_block: Option<String>,
+    pub add_repositories_rebuild: Option<String>,
+    pub rev: Option<String>,
+    pub force: bool,
+    pub missingok: bool,
+    pub newinstance: bool,
+    pub noservice: bool,
+    pub autocleanup: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CopyOptions {
+    /// Revision of the origin to copy, the latest revision if unset
+    pub rev: Option<String>,
+    pub expand: bool,
+    pub keeplink: bool,
+    pub repairlink: bool,
+    pub withhistory: bool,
+    pub withvrev: bool,
+    pub noservice: bool,
+    pub comment: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LinkOptions {
+    /// Link to a fixed revision rather than following the origin
+    pub rev: Option<String>,
+    pub cicount: Option<String>,
+    /// Disable publishing when creating the target package
+    pub disable_publish: bool,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename = "link")]
+struct LinkFile<'a> {
+    project: &'a str,
+    package: &'a str,
+    rev: Option<&'a str>,
+    cicount: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageBuilder<'a> {
+    pub client: &'a Client,
+    pub project: String,
+    pub package: String,
+}
+
+impl<'a> PackageBuilder<'a> {
+    fn full_request(&self, repository: &str, arch: &str, command: &str) -> Result<Url> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("build")
+            .push(&self.project)
+            .push(repository)
+            .push(arch)
+            .push(&self.package)
+            .push(command);
+        Ok(u)
+    }
+
+    /// List the binaries built for this package
+    pub async fn binaries(&self, repository: &str, arch: &str) -> Result<BinaryList> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("build")
+            .push(&self.project)
+            .push(repository)
+            .push(arch)
+            .push(&self.package);
+        self.client.fetch(u).await
+    }
+
+    /// Stream the contents of a single binary built for this package
+    pub fn binary(&self, repository: &str, arch: &str, filename: &str) -> Result<DownloadStream> {
+        let u = self.full_request(repository, arch, filename)?;
+        Ok(DownloadStream::new(self.client.get(u)))
+    }
+
+    pub async fn buildinfo(&self, repository: &str, arch: &str) -> Result<BuildInfo> {
+        let u = self.full_request(repository, arch, "_buildinfo")?;
+        self.client.fetch(u).await
+    }
+
+    /// Build info for a local build recipe (e.g. a spec or dsc file) rather
+    /// than the committed sources
+    ///
+    /// For a package which doesn't exist on the server use the `_repository`
+    /// pseudo package.
+    pub async fn buildinfo_for_recipe<B: Into<reqwest::Body>>(
+        &self,
+        repository: &str,
+        arch: &str,
+        recipe: B,
+    ) -> Result<BuildInfo> {
+        let u = self.full_request(repository, arch, "_buildinfo")?;
+        self.client
+            .send_and_parse(self.client.post(u).body(recipe))
+            .await
+    }
+
+    pub async fn jobstatus(&self, repository: &str, arch: &str) -> Result<JobStatus> {
+        let u = self.full_request(repository, arch, "_jobstatus")?;
+        self.client.fetch(u).await
+    }
+
+    pub async fn jobhistory(
+        &self,
+        repository: &str,
+        arch: &str,
+        options: &JobHistoryOptions,
+    ) -> Result<JobHistory> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("build")
+            .push(&self.project)
+            .push(repository)
+            .push(arch)
+            .push("_jobhistory");
+        u.query_pairs_mut().append_pair("package", &self.package);
+        options.append_query(&mut u);
+        self.client.fetch(u).await
+    }
+
+    pub async fn history(&self, repository: &str, arch: &str) -> Result<BuildHistory> {
+        let u = self.full_request(repository, arch, "_history")?;
+        self.client.fetch(u).await
+    }
+
+    pub async fn status(&self, repository: &str, arch: &str) -> Result<BuildStatus> {
+        let u = self.full_request(repository, arch, "_status")?;
+        self.client.fetch(u).await
+    }
+
+    async fn build_command(&self, command: &str, options: &BuildCommandOptions) -> Result<()> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("build")
+            .push(&self.project);
+        u.query_pairs_mut()
+            .append_pair("cmd", command)
+            .append_pair("package", &self.package);
+        options.append_query(&mut u);
+        Client::send_with_error(self.client.post(u)).await?;
+        Ok(())
+    }
+
+    /// Trigger a rebuild
+    pub async fn rebuild(&self, options: &BuildCommandOptions) -> Result<()> {
+        self.build_command("rebuild", options).await
+    }
+
+    /// Abort running builds
+    pub async fn abort_build(&self, options: &BuildCommandOptions) -> Result<()> {
+        self.build_command("abortbuild", options).await
+    }
+
+    /// Restart running builds
+    pub async fn restart_build(&self, options: &BuildCommandOptions) -> Result<()> {
+        self.build_command("restartbuild", options).await
+    }
+
+    /// Remove build results
+    pub async fn wipe(&self, options: &BuildCommandOptions) -> Result<()> {
+        self.build_command("wipe", options).await
+    }
+
+    /// Kill running builds without waiting for the workers to respond
+    pub async fn kill_build(&self, options: &BuildCommandOptions) -> Result<()> {
+        self.build_command("killbuild", options).await
+    }
+
+    pub fn log(&self, repository: &str, arch: &str) -> PackageLog<'_> {
+        PackageLog {
+            client: self.client,
+            project: self.project.clone(),
+            package: self.package.clone(),
+            repository: repository.to_owned(),
+            arch: arch.to_owned(),
+        }
+    }
+
+    fn source_request(&self) -> Result<Url> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source")
+            .push(&self.project)
+            .push(&self.package);
+        Ok(u)
+    }
+
+    fn source_file_request(&self, file: &str) -> Result<Url> {
+        let mut u = self.source_request()?;
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push(file);
+        Ok(u)
+    }
+
+    pub async fn list(&self) -> Result<Directory> {
+        self.list_with_options(&ListOptions::default()).await
+    }
+
+    fn list_request(&self, options: &ListOptions) -> Result<Url> {
+        let mut u = self.source_request()?;
+        if let Some(rev) = &options.rev {
+            u.query_pairs_mut().append_pair("rev", rev);
+        }
+        if options.expand {
+            u.query_pairs_mut().append_pair("expand", "1");
+        }
+        if let Some(linkrev) = &options.linkrev {
+            u.query_pairs_mut().append_pair("linkrev", linkrev);
+        }
+        if options.meta {
+            u.query_pairs_mut().append_pair("meta", "1");
+        }
+        if options.deleted {
+            u.query_pairs_mut().append_pair("deleted", "1");
+        }
+        Ok(u)
+    }
+
+    pub async fn list_with_options(&self, options: &ListOptions) -> Result<Directory> {
+        let u = self.list_request(options)?;
+        self.client.fetch(u).await
+    }
+
+    /// Summary of the sources (`view=info`), including the md5 used to
+    /// decide whether a package needs rebuilding
+    pub async fn source_info(&self, options: &ListOptions) -> Result<SourceInfo> {
+        let mut u = self.list_request(options)?;
+        u.query_pairs_mut().append_pair("view", "info");
+        self.client.fetch(u).await
+    }
+
+    /// Address a multibuild flavor of this package
+    ///
+    /// This is only meaningful for build operations; sources are only
+    /// available from the package itself.
+    pub fn flavor(&self, flavor: &str) -> PackageBuilder<'a> {
+        PackageBuilder {
+            client: self.client,
+            project: self.project.clone(),
+            package: format!("{}:{}", self.package, flavor),
+        }
+    }
+
+    /// The `_multibuild` file of the package
+    pub async fn multibuild(&self) -> Result<Multibuild> {
+        let u = self.source_file_request("_multibuild")?;
+        self.client.fetch(u).await
+    }
+
+    pub async fn source_history(&self) -> Result<SourceRevisionList> {
+        let u = self.source_file_request("_history")?;
+        self.client.fetch(u).await
+    }
+
+    pub async fn meta(&self) -> Result<PackageMeta> {
+        let u = self.source_file_request("_meta")?;
+        let data = Client::send_with_error(self.client.get(u))
+            .await?
+            .text()
+            .await?;
+        PackageMeta::from_xml(&data)
+    }
+
+    /// Store the package meta, creating the package if it doesn't exist yet
+    pub async fn set_meta(&self, meta: &PackageMeta) -> Result<()> {
+        let u = self.source_file_request("_meta")?;
+        Client::send_with_error(self.client.put(u).body(meta.to_xml()?)).await?;
+        Ok(())
+    }
+
+    fn diff_request(&self, options: &DiffOptions) -> Result<Url> {
+        let mut u = self.source_request()?;
+        u.query_pairs_mut().append_pair("cmd", "diff");
+        if let Some(rev) = &options.rev {
+            u.query_pairs_mut().append_pair("rev", rev);
+        }
+        if let Some(oproject) = &options.oproject {
+            u.query_pairs_mut().append_pair("oproject", oproject);
+        }
+        if let Some(opackage) = &options.opackage {
+            u.query_pairs_mut().append_pair("opackage", opackage);
+        }
+        if let Some(orev) = &options.orev {
+            u.query_pairs_mut().append_pair("orev", orev);
+        }
+        if let Some(linkrev) = &options.linkrev {
+            u.query_pairs_mut().append_pair("linkrev", linkrev);
+        }
+        if let Some(olinkrev) = &options.olinkrev {
+            u.query_pairs_mut().append_pair("olinkrev", olinkrev);
+        }
+        if options.expand {
+            u.query_pairs_mut().append_pair("expand", "1");
+        }
+        if options.unified {
+            u.query_pairs_mut().append_pair("unified", "1");
+        }
+        if options.meta {
+            u.query_pairs_mut().append_pair("meta", "1");
+        }
+        Ok(u)
+    }
+
+    /// Textual diff between two revisions of the sources
+    pub async fn diff(&self, options: &DiffOptions) -> Result<String> {
+        let u = self.diff_request(options)?;
+        Ok(Client::send_with_error(self.client.post(u))
+            .await?
+            .text()
+            .await?)
+    }
+
+    /// Structured diff between two revisions of the sources
+    pub async fn diff_xml(&self, options: &DiffOptions) -> Result<SourceDiff> {
+        let mut u = self.diff_request(options)?;
+        u.query_pairs_mut().append_pair("view", "xml");
+        self.client.send_and_parse(self.client.post(u)).await
+    }
+
+    pub async fn delete(&self, options: &DeleteOptions) -> Result<()> {
+        let mut u = self.source_request()?;
+        options.append_query(&mut u);
+        Client::send_with_error(self.client.delete(u)).await?;
+        Ok(())
+    }
+
+    fn comments_request(&self) -> Result<Url> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("comments")
+            .push("package")
+            .push(&self.project)
+            .push(&self.package);
+        Ok(u)
+    }
+
+    pub async fn comments(&self) -> Result<CommentList> {
+        let u = self.comments_request()?;
+        self.client.fetch(u).await
+    }
+
+    /// Add a comment, optionally as a reply to an existing one
+    pub async fn add_comment(&self, text: &str, parent: Option<u64>) -> Result<()> {
+        let u = self.comments_request()?;
+        self.client.post_comment(u, text, parent).await
+    }
+
+    /// Restore a deleted package
+    pub async fn undelete(&self, comment: Option<&str>) -> Result<()> {
+        let mut u = self.source_request()?;
+        u.query_pairs_mut().append_pair("cmd", "undelete");
+        if let Some(comment) = comment {
+            u.query_pairs_mut().append_pair("comment", comment);
+        }
+        Client::send_with_error(self.client.post(u)).await?;
+        Ok(())
+    }
+
+    /// Branch this package, returning the newly created package
+    pub async fn branch(&self, options: &BranchOptions) -> Result<PackageBuilder<'a>> {
+        let mut u = self.source_request()?;
+        u.query_pairs_mut().append_pair("cmd", "branch");
+        if let Some(target_project) = &options.target_project {
+            u.query_pairs_mut()
+                .append_pair("target_project", target_project);
+        }
+        if let Some(target_package) = &options.target_package {
+            u.query_pairs_mut()
+                .append_pair("target_package", target_package);
+        }
+        if options.add_repositories {
+            u.query_pairs_mut().append_pair("add_repositories", "1");
+        }
+        if let Some(block) = &options.add_repositories_block {
+            u.query_pairs_mut()
+                .append_pair("add_repositories_block", block);
+        }
+        if let Some(rebuild) = &options.add_repositories_rebuild {
+            u.query_pairs_mut()
+                .append_pair("add_repositories_rebuild", rebuild);
+        }
+        if let Some(rev) = &options.rev {
+            u.query_pairs_mut().append_pair("rev", rev);
+        }
+        if options.force {
+            u.query_pairs_mut().append_pair("force", "1");
+        }
+        if options.missingok {
+            u.query_pairs_mut().append_pair("missingok", "1");
+        }
+        if options.newinstance {
+            u.query_pairs_mut().append_pair("newinstance", "1");
+        }
+        if options.noservice {
+            u.query_pairs_mut().append_pair("noservice", "1");
+        }
+        if options.autocleanup {
+            u.query_pairs_mut().append_pair("autocleanup", "1");
+        }
+
+        let status: Status = self.client.send_and_parse(self.client.post(u)).await?;
+        match (status.data("targetproject"), status.data("targetpackage")) {
+            (Some(project), Some(package)) => Ok(PackageBuilder {
+                client: self.client,
+                project: project.to_owned(),
+                package: package.to_owned(),
+            }),
+            _ => Err(Error::UnexpectedResult),
+        }
+    }
+
+    /// Copy the sources of this package to another package, creating it if
+    /// needed
+    pub async fn copy_to(
+        &self,
+        target_project: String,
+        target_package: String,
+        options: &CopyOptions,
+    ) -> Result<PackageBuilder<'a>> {
+        let target = PackageBuilder {
+            client: self.client,
+            project: target_project,
+            package: target_package,
+        };
+
+        let mut u = target.source_request()?;
+        u.query_pairs_mut().append_pair("cmd", "copy");
+        u.query_pairs_mut().append_pair("oproject", &self.project);
+        u.query_pairs_mut().append_pair("opackage", &self.package);
+        if let Some(rev) = &options.rev {
+            u.query_pairs_mut().append_pair("orev", rev);
+        }
+        if options.expand {
+            u.query_pairs_mut().append_pair("expand", "1");
+        }
+        if options.keeplink {
+            u.query_pairs_mut().append_pair("keeplink", "1");
+        }
+        if options.repairlink {
+            u.query_pairs_mut().append_pair("repairlink", "1");
+        }
+        if options.withhistory {
+            u.query_pairs_mut().append_pair("withhistory", "1");
+        }
+        if options.withvrev {
+            u.query_pairs_mut().append_pair("withvrev", "1");
+        }
+        if options.noservice {
+            u.query_pairs_mut().append_pair("noservice", "1");
+        }
+        if let Some(comment) = &options.comment {
+            u.query_pairs_mut().append_pair("comment", comment);
+        }
+
+        Client::send_with_error(self.client.post(u)).await?;
+        Ok(target)
+    }
+
+    /// Create a package linking to this one, like `osc linkpac`
+    ///
+    /// If the target package doesn't exist yet its meta is based on the meta
+    /// of this package.
+    pub async fn link_to(
+        &self,
+        target_project: String,
+        target_package: String,
+        options: &LinkOptions,
+    ) -> Result<PackageBuilder<'a>> {
+        let target = PackageBuilder {
+            client: self.client,
+            project: target_project,
+            package: target_package,
+        };
+
+        match target.meta().await {
+            Ok(_) => (),
+            Err(Error::ApiError(e)) if e.code == "unknown_package" => {
+                let mut meta = self.meta().await?;
+                meta.name = target.package.clone();
+                meta.project = target.project.clone();
+                meta.devel = None;
+                meta.scmsync = None;
+                meta.lock = None;
+                meta.persons.clear();
+                meta.groups.clear();
+                meta.unknown.remove_elements("releasename");
+                if options.disable_publish {
+                    meta.publish = Some(Flags {
+                        flags: vec![Flag::Disable {
+                            repository: None,
+                            arch: None,
+                        }],
+                    });
+                }
+                target.set_meta(&meta).await?;
+            }
+            Err(e) => return Err(e),
+        }
+
+        let link = LinkFile {
+            project: &self.project,
+            package: &self.package,
+            rev: options.rev.as_deref(),
+            cicount: options.cicount.as_deref(),
+        };
+        let u = target.source_file_request("_link")?;
+        Client::send_with_error(self.client.put(u).body(quick_xml::se::to_string(&link)?)).await?;
+
+        Ok(target)
+    }
+
+    /// The `_service` file of the package
+    pub async fn service(&self) -> Result<Services> {
+        let u = self.source_file_request("_service")?;
+        self.client.fetch(u).await
+    }
+
+    /// Replace the `_service` file of the package, creating a new revision
+    pub async fn set_service(&self, services: &Services, options: &CommitOptions) -> Result<()> {
+        let mut u = self.source_file_request("_service")?;
+        if let Some(comment) = &options.comment {
+            u.query_pairs_mut().append_pair("comment", comment);
+        }
+        Client::send_with_error(self.client.put(u).body(services.to_xml()?)).await?;
+        Ok(())
+    }
+
+    /// State of the last service run, `None` if the package has no services
+    pub async fn service_info(&self) -> Result<Option<ServiceInfo>> {
+        Ok(self.list().await?.serviceinfo)
+    }
+
+    async fn service_command(&self, command: &str, comment: Option<&str>) -> Result<()> {
+        let mut u = self.source_request()?;
+        u.query_pairs_mut().append_pair("cmd", command);
+        if let Some(comment) = comment {
+            u.query_pairs_mut().append_pair("comment", comment);
+        }
+        Client::send_with_error(self.client.post(u)).await?;
+        Ok(())
+    }
+
+    /// Trigger a run of the server side services
+    pub async fn run_service(&self) -> Result<()> {
+        self.service_command("runservice", None).await
+    }
+
+    /// Wait for running services to finish
+    pub async fn wait_service(&self) -> Result<()> {
+        self.service_command("waitservice", None).await
+    }
+
+    /// Commit the files generated by the services and remove the `_service`
+    /// file
+    pub async fn merge_service(&self, comment: Option<&str>) -> Result<()> {
+        self.service_command("mergeservice", comment).await
+    }
+
+    /// Upload a file to the pending revision, to be committed by a later call to `commit`
+    pub async fn upload_for_commit<B: Into<reqwest::Body>>(
+        &self,
+        file: &str,
+        data: B,
+    ) -> Result<()> {
+        let mut u = self.source_file_request(file)?;
+        u.query_pairs_mut().append_pair("rev", "upload");
+        Client::send_with_error(self.client.put(u).body(data)).await?;
+        Ok(())
+    }
+
+    /// Delete a file, directly creating a new revision without it
+    pub async fn delete_file(&self, file: &str, options: &CommitOptions) -> Result<()> {
+        let mut u = self.source_file_request(file)?;
+        if let Some(comment) = &options.comment {
+            u.query_pairs_mut().append_pair("comment", comment);
+        }
+        Client::send_with_error(self.client.delete(u)).await?;
+        Ok(())
+    }
+
+    /// Commit a new revision consisting of exactly the files in `filelist`
+    pub async fn commit(
+        &self,
+        filelist: &CommitFileList,
+        options: &CommitOptions,
+    ) -> Result<CommitResult> {
+        let mut u = self.source_request()?;
+        u.query_pairs_mut().append_pair("cmd", "commitfilelist");
+        if let Some(comment) = &options.comment {
+            u.query_pairs_mut().append_pair("comment", comment);
+        }
+
+        let body = quick_xml::se::to_string(filelist)?;
+        let data = Client::send_with_error(self.client.post(u).body(body))
+            .await?
+            .text()
+            .await?;
+
+        let response: CommitResponse = quick_xml::de::from_str(&data)?;
+        if response.error.as_deref() == Some("missing") {
+            Ok(CommitResult::MissingEntries(response.entries))
+        } else {
+            Ok(CommitResult::Success(Box::new(quick_xml::de::from_str(
+                &data,
+            )?)))
+        }
+    }
+
+    /// Stream the contents of a source file
+    pub fn file(&self, file: &str, options: &FileOptions) -> Result<DownloadStream> {
+        let mut u = self.source_file_request(file)?;
+        if let Some(rev) = &options.rev {
+            u.query_pairs_mut().append_pair("rev", rev);
+        }
+        if options.expand {
+            u.query_pairs_mut().append_pair("expand", "1");
+        }
+        Ok(DownloadStream::new(self.client.get(u)))
+    }
+
+    pub async fn result(&self) -> Result<ResultList> {
+        self.result_with_options(&ResultOptions::default()).await
+    }
+
+    fn result_request(&self, options: &ResultOptions) -> Result<Url> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("build")
+            .push(&self.project)
+            .push("_result");
+        u.query_pairs_mut().append_pair("package", &self.package);
+        options.append_query(&mut u);
+        Ok(u)
+    }
+
+    pub async fn result_with_options(&self, options: &ResultOptions) -> Result<ResultList> {
+        let u = self.result_request(options)?;
+        self.client.fetch(u).await
+    }
+
+    /// Stream the current build results followed by each change to them.
+    ///
+    /// Changes are long-polled from the server; transient network errors are
+    /// retried with a backoff, other errors end the stream.
+    pub fn watch(&self, options: &ResultOptions) -> Result<BoxStream<'a, Result<ResultList>>> {
+        let u = self.result_request(options)?;
+        Ok(watch_results(self.client, u))
+    }
+}
+
+pub struct ProjectBuilder<'a> {
     client: &'a Client,
     project: String,
-    package: String,
-    repository: String,
-    arch: String,
 }
 
-impl PackageLog<'_> {
-    fn request(&self) -> Result<Url> {
+impl<'a> ProjectBuilder<'a> {
+    pub fn package(self, package: String) -> PackageBuilder<'a> {
+        PackageBuilder {
+            client: self.client,
+            project: self.project,
+            package,
+        }
+    }
+
+    fn source_request(&self) -> Result<Url> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
             .map_err(|_| Error::InvalidUrl)?
-            .push("build")
-            .push(&self.project)
-            .push(&self.repository)
-            .push(&self.arch)
-            .push(&self.package)
-            .push("_log");
+            .push("source")
+            .push(&self.project);
         Ok(u)
     }
 
-    pub fn stream(&self, offset: usize) -> Result<PackageLogStream> {
-        let u = self.request()?;
-        println!("Requesting: {:?}", u);
-        Ok(PackageLogStream::new(self.client, offset, u))
+    fn source_file_request(&self, file: &str) -> Result<Url> {
+        let mut u = self.source_request()?;
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push(file);
+        Ok(u)
     }
 
-    /// Returns size and mtime
-    pub async fn entry(&self) -> Result<(usize, u64)> {
-        let mut u = self.request()?;
-        u.query_pairs_mut().append_pair("view", "entry");
+    /// Names of all packages in the project
+    pub async fn packages(&self) -> Result<Vec<String>> {
+        let u = self.source_request()?;
+        let list: DirectoryList = self.client.fetch(u).await?;
+        Ok(list.names())
+    }
 
-        let e: LogEntry = self.client.request(u).await?;
-        if let Some(entry) = e.entries.get(0) {
-            Ok((entry.size, entry.mtime))
-        } else {
-            Err(Error::UnexpectedResult)
+    /// Names of the deleted packages of the project which can be restored
+    pub async fn deleted_packages(&self) -> Result<Vec<String>> {
+        let mut u = self.source_request()?;
+        u.query_pairs_mut().append_pair("deleted", "1");
+        let list: DirectoryList = self.client.fetch(u).await?;
+        Ok(list.names())
+    }
+
+    pub async fn delete(&self, options: &DeleteOptions) -> Result<()> {
+        let mut u = self.source_request()?;
+        options.append_query(&mut u);
+        Client::send_with_error(self.client.delete(u)).await?;
+        Ok(())
+    }
+
+    fn comments_request(&self) -> Result<Url> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("comments")
+            .push("project")
+            .push(&self.project);
+        Ok(u)
+    }
+
+    pub async fn comments(&self) -> Result<CommentList> {
+        let u = self.comments_request()?;
+        self.client.fetch(u).await
+    }
+
+    /// Add a comment, optionally as a reply to an existing one
+    pub async fn add_comment(&self, text: &str, parent: Option<u64>) -> Result<()> {
+        let u = self.comments_request()?;
+        self.client.post_comment(u, text, parent).await
+    }
+
+    /// Restore a deleted project
+    pub async fn undelete(&self, comment: Option<&str>) -> Result<()> {
+        let mut u = self.source_request()?;
+        u.query_pairs_mut().append_pair("cmd", "undelete");
+        if let Some(comment) = comment {
+            u.query_pairs_mut().append_pair("comment", comment);
         }
+        Client::send_with_error(self.client.post(u)).await?;
+        Ok(())
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct PackageBuilder<'a> {
-    pub client: &'a Client,
-    pub project: String,
-    pub package: String,
-}
+    /// Source summary of all packages in the project (`view=info`)
+    pub async fn source_info(&self) -> Result<SourceInfoList> {
+        let mut u = self.source_request()?;
+        u.query_pairs_mut().append_pair("view", "info");
+        self.client.fetch(u).await
+    }
 
-impl PackageBuilder<'_> {
-    fn full_request(&self, repository: &str, arch: &str, command: &str) -> Result<Url> {
+    pub async fn meta(&self) -> Result<ProjectMeta> {
+        let u = self.source_file_request("_meta")?;
+        let data = Client::send_with_error(self.client.get(u))
+            .await?
+            .text()
+            .await?;
+        ProjectMeta::from_xml(&data)
+    }
+
+    /// Store the project meta, creating the project if it doesn't exist yet
+    pub async fn set_meta(&self, meta: &ProjectMeta) -> Result<()> {
+        let u = self.source_file_request("_meta")?;
+        Client::send_with_error(self.client.put(u).body(meta.to_xml()?)).await?;
+        Ok(())
+    }
+
+    pub async fn result(&self) -> Result<ResultList> {
+        self.result_with_options(&ResultOptions::default()).await
+    }
+
+    fn result_request(&self, options: &ResultOptions) -> Result<Url> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
             .map_err(|_| Error::InvalidUrl)?
             .push("build")
             .push(&self.project)
-            .push(repository)
-            .push(arch)
-            .push(&self.package)
-            .push(command);
+            .push("_result");
+        options.append_query(&mut u);
         Ok(u)
     }
 
-    pub async fn jobstatus(&self, repository: &str, arch: &str) -> Result<JobStatus> {
-        let u = self.full_request(repository, arch, "_jobstatus")?;
-        self.client.request(u).await
+    pub async fn result_with_options(&self, options: &ResultOptions) -> Result<ResultList> {
+        let u = self.result_request(options)?;
+        self.client.fetch(u).await
     }
 
-    pub async fn history(&self, repository: &str, arch: &str) -> Result<BuildHistory> {
-        let u = self.full_request(repository, arch, "_history")?;
-        self.client.request(u).await
+    /// Package counts per build state for each repository and architecture,
+    /// without the individual package results
+    pub async fn summary(&self) -> Result<ProjectSummary> {
+        let list = self
+            .result_with_options(&ResultOptions {
+                views: vec![ResultView::Summary],
+                ..Default::default()
+            })
+            .await?;
+        Ok(list.into())
     }
 
-    pub async fn status(&self, repository: &str, arch: &str) -> Result<BuildStatus> {
-        let u = self.full_request(repository, arch, "_status")?;
-        self.client.request(u).await
+    /// Stream the current build results followed by each change to them.
+    ///
+    /// Changes are long-polled from the server; transient network errors are
+    /// retried with a backoff, other errors end the stream.
+    pub fn watch(&self, options: &ResultOptions) -> Result<BoxStream<'a, Result<ResultList>>> {
+        let u = self.result_request(options)?;
+        Ok(watch_results(self.client, u))
     }
 
-    pub fn log(&self, repository: &str, arch: &str) -> PackageLog<'_> {
-        PackageLog {
-            client: self.client,
-            project: self.project.clone(),
-            package: self.package.clone(),
-            repository: repository.to_owned(),
-            arch: arch.to_owned(),
-        }
+    fn repository_request(&self, repository: &str, arch: &str) -> Result<Url> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("build")
+            .push(&self.project)
+            .push(repository)
+            .push(arch)
+            .push("_repository");
+        Ok(u)
     }
 
-    pub async fn list(&self) -> Result<Directory> {
+    /// List the binaries available in a repository, e.g. for use as build
+    /// dependencies
+    pub async fn repository_binaries(&self, repository: &str, arch: &str) -> Result<BinaryList> {
+        let u = self.repository_request(repository, arch)?;
+        self.client.fetch(u).await
+    }
+
+    /// Stream the contents of a single binary from a repository
+    pub fn repository_binary(
+        &self,
+        repository: &str,
+        arch: &str,
+        filename: &str,
+    ) -> Result<DownloadStream> {
+        let mut u = self.repository_request(repository, arch)?;
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push(filename);
+        Ok(DownloadStream::new(self.client.get(u)))
+    }
+
+    pub async fn builddepinfo(
+        &self,
+        repository: &str,
+        arch: &str,
+        options: &BuildDepInfoOptions,
+    ) -> Result<BuildDepInfo> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
             .map_err(|_| Error::InvalidUrl)?
-            .push("source")
+            .push("build")
             .push(&self.project)
-            .push(&self.package);
-        self.client.request(u).await
+            .push(repository)
+            .push(arch)
+            .push("_builddepinfo");
+        for package in &options.packages {
+            u.query_pairs_mut().append_pair("package", package);
+        }
+        if let Some(view) = options.view {
+            u.query_pairs_mut().append_pair("view", view.as_str());
+        }
+        self.client.fetch(u).await
     }
 
-    pub async fn result(&self) -> Result<ResultList> {
+    pub async fn jobhistory(
+        &self,
+        repository: &str,
+        arch: &str,
+        options: &JobHistoryOptions,
+    ) -> Result<JobHistory> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
             .map_err(|_| Error::InvalidUrl)?
             .push("build")
             .push(&self.project)
-            .push("_result");
-        u.query_pairs_mut().append_pair("package", &self.package);
-        self.client.request(u).await
+            .push(repository)
+            .push(arch)
+            .push("_jobhistory");
+        options.append_query(&mut u);
+        self.client.fetch(u).await
+    }
+
+    /// All packages in the repository which get rebuilt, directly or
+    /// indirectly, when `package` changes
+    pub async fn reverse_dependencies(
+        &self,
+        repository: &str,
+        arch: &str,
+        package: &str,
+    ) -> Result<Vec<String>> {
+        let options = BuildDepInfoOptions {
+            packages: Vec::new(),
+            view: Some(BuildDepInfoView::RevPkgNames),
+        };
+        let info = self.builddepinfo(repository, arch, &options).await?;
+        Ok(info.transitive_pkgdeps(package))
+    }
+
+    async fn build_command(&self, command: &str, options: &BuildCommandOptions) -> Result<()> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("build")
+            .push(&self.project);
+        u.query_pairs_mut().append_pair("cmd", command);
+        options.append_query(&mut u);
+        Client::send_with_error(self.client.post(u)).await?;
+        Ok(())
+    }
+
+    /// Trigger a rebuild
+    pub async fn rebuild(&self, options: &BuildCommandOptions) -> Result<()> {
+        self.build_command("rebuild", options).await
+    }
+
+    /// Abort running builds
+    pub async fn abort_build(&self, options: &BuildCommandOptions) -> Result<()> {
+        self.build_command("abortbuild", options).await
+    }
+
+    /// Restart running builds
+    pub async fn restart_build(&self, options: &BuildCommandOptions) -> Result<()> {
+        self.build_command("restartbuild", options).await
+    }
+
+    /// Remove build results
+    pub async fn wipe(&self, options: &BuildCommandOptions) -> Result<()> {
+        self.build_command("wipe", options).await
+    }
+
+    /// Kill running builds without waiting for the workers to respond
+    pub async fn kill_build(&self, options: &BuildCommandOptions) -> Result<()> {
+        self.build_command("killbuild", options).await
     }
 }
 
-pub struct ProjectBuilder<'a> {
-    client: &'a Client,
-    project: String,
+#[derive(Debug, Clone)]
+pub struct RequestHandle<'a> {
+    pub client: &'a Client,
+    pub id: u64,
 }
 
-impl<'a> ProjectBuilder<'a> {
-    pub fn package(self, package: String) -> PackageBuilder<'a> {
-        PackageBuilder {
-            client: self.client,
-            project: self.project,
-            package,
-        }
+impl<'a> RequestHandle<'a> {
+    fn request_url(&self) -> Result<Url> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("request")
+            .push(&self.id.to_string());
+        Ok(u)
     }
 
-    pub async fn result(&self) -> Result<ResultList> {
+    pub async fn get(&self) -> Result<Request> {
+        let mut u = self.request_url()?;
+        u.query_pairs_mut().append_pair("withhistory", "1");
+        self.client.fetch(u).await
+    }
+
+    fn comments_request(&self) -> Result<Url> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
             .map_err(|_| Error::InvalidUrl)?
-            .push("build")
-            .push(&self.project)
-            .push("_result");
-        self.client.request(u).await
+            .push("comments")
+            .push("request")
+            .push(&self.id.to_string());
+        Ok(u)
+    }
+
+    pub async fn comments(&self) -> Result<CommentList> {
+        let u = self.comments_request()?;
+        self.client.fetch(u).await
+    }
+
+    /// Add a comment, optionally as a reply to an existing one
+    pub async fn add_comment(&self, text: &str, parent: Option<u64>) -> Result<()> {
+        let u = self.comments_request()?;
+        self.client.post_comment(u, text, parent).await
+    }
+
+    fn command_request(&self, command: &str, comment: Option<&str>) -> Result<Url> {
+        let mut u = self.request_url()?;
+        u.query_pairs_mut().append_pair("cmd", command);
+        if let Some(comment) = comment {
+            u.query_pairs_mut().append_pair("comment", comment);
+        }
+        Ok(u)
+    }
+
+    fn diff_request(&self, options: &RequestDiffOptions) -> Result<Url> {
+        let mut u = self.command_request("diff", None)?;
+        options.append_query(&mut u);
+        Ok(u)
+    }
+
+    /// Diff of the changes the request would make, as text
+    pub async fn diff(&self, options: &RequestDiffOptions) -> Result<String> {
+        let u = self.diff_request(options)?;
+        Ok(Client::send_with_error(self.client.post(u))
+            .await?
+            .text()
+            .await?)
+    }
+
+    /// Structured diff of the changes the request would make, per action
+    pub async fn diff_xml(&self, options: &RequestDiffOptions) -> Result<RequestDiff> {
+        let mut u = self.diff_request(options)?;
+        u.query_pairs_mut().append_pair("view", "xml");
+        self.client.send_and_parse(self.client.post(u)).await
+    }
+
+    async fn change_state(&self, state: RequestState, comment: Option<&str>) -> Result<()> {
+        let mut u = self.command_request("changestate", comment)?;
+        u.query_pairs_mut().append_pair("newstate", state.as_str());
+        Client::send_with_error(self.client.post(u)).await?;
+        Ok(())
+    }
+
+    pub async fn accept(&self, comment: Option<&str>) -> Result<()> {
+        self.change_state(RequestState::Accepted, comment).await
+    }
+
+    pub async fn decline(&self, comment: Option<&str>) -> Result<()> {
+        self.change_state(RequestState::Declined, comment).await
+    }
+
+    /// Withdraw the request, only possible for its creator
+    pub async fn revoke(&self, comment: Option<&str>) -> Result<()> {
+        self.change_state(RequestState::Revoked, comment).await
+    }
+
+    /// Replace the request by a newer one
+    pub async fn supersede(&self, superseded_by: u64, comment: Option<&str>) -> Result<()> {
+        let mut u = self.command_request("changestate", comment)?;
+        u.query_pairs_mut()
+            .append_pair("newstate", RequestState::Superseded.as_str())
+            .append_pair("superseded_by", &superseded_by.to_string());
+        Client::send_with_error(self.client.post(u)).await?;
+        Ok(())
+    }
+
+    pub async fn add_review(&self, reviewer: &Reviewer, comment: Option<&str>) -> Result<()> {
+        let mut u = self.command_request("addreview", comment)?;
+        reviewer.append_query(&mut u);
+        Client::send_with_error(self.client.post(u)).await?;
+        Ok(())
+    }
+
+    async fn change_review_state(
+        &self,
+        reviewer: &Reviewer,
+        state: &str,
+        comment: Option<&str>,
+    ) -> Result<()> {
+        let mut u = self.command_request("changereviewstate", comment)?;
+        u.query_pairs_mut().append_pair("newstate", state);
+        reviewer.append_query(&mut u);
+        Client::send_with_error(self.client.post(u)).await?;
+        Ok(())
+    }
+
+    pub async fn accept_review(&self, reviewer: &Reviewer, comment: Option<&str>) -> Result<()> {
+        self.change_review_state(reviewer, "accepted", comment)
+            .await
+    }
+
+    pub async fn decline_review(&self, reviewer: &Reviewer, comment: Option<&str>) -> Result<()> {
+        self.change_review_state(reviewer, "declined", comment)
+            .await
     }
 }
 
@@ -427,13 +3903,90 @@ impl Client {
         }
     }
 
-    pub fn project(&self, project: String) -> ProjectBuilder {
+    pub fn project(&self, project: String) -> ProjectBuilder<'_> {
         ProjectBuilder {
             client: self,
             project,
         }
     }
 
+    /// Names of all projects on the server
+    pub async fn projects(&self) -> Result<Vec<String>> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source");
+        let list: DirectoryList = self.fetch(u).await?;
+        Ok(list.names())
+    }
+
+    pub fn request(&self, id: u64) -> RequestHandle<'_> {
+        RequestHandle { client: self, id }
+    }
+
+    /// Create a new request, returning it as stored by the server
+    pub async fn create_request(&self, request: &NewRequest) -> Result<Request> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("request");
+        u.query_pairs_mut().append_pair("cmd", "create");
+        let body = quick_xml::se::to_string(request)?;
+        self.send_and_parse(self.post(u).body(body)).await
+    }
+
+    /// Requests matching the given filters
+    pub async fn requests(&self, options: &RequestListOptions) -> Result<Vec<Request>> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("request");
+        u.query_pairs_mut().append_pair("view", "collection");
+        options.append_query(&mut u);
+        let collection: RequestCollection = self.fetch(u).await?;
+        Ok(collection.requests)
+    }
+
+    /// Delete a comment of a project, package or request
+    pub async fn delete_comment(&self, id: u64) -> Result<()> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("comment")
+            .push(&id.to_string());
+        Client::send_with_error(self.delete(u)).await?;
+        Ok(())
+    }
+
+    async fn post_comment(&self, mut url: Url, text: &str, parent: Option<u64>) -> Result<()> {
+        if let Some(parent) = parent {
+            url.query_pairs_mut()
+                .append_pair("parent_id", &parent.to_string());
+        }
+        Client::send_with_error(self.post(url).body(text.to_string())).await?;
+        Ok(())
+    }
+
+    /// Status of the build workers, their queues and the scheduler daemons
+    pub async fn worker_status(&self) -> Result<WorkerStatus> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("worker")
+            .push("_status");
+        self.fetch(u).await
+    }
+
+    /// Worker status as reported directly by the build backend
+    pub async fn build_worker_status(&self) -> Result<WorkerStatus> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("build")
+            .push("_workerstatus");
+        self.fetch(u).await
+    }
+
     fn get(&self, url: Url) -> RequestBuilder {
         self
             .client
@@ -441,6 +3994,24 @@ impl Client {
             .basic_auth(&self.user, Some(&self.pass))
     }
 
+    fn post(&self, url: Url) -> RequestBuilder {
+        self.client
+            .post(url)
+            .basic_auth(&self.user, Some(&self.pass))
+    }
+
+    fn put(&self, url: Url) -> RequestBuilder {
+        self.client
+            .put(url)
+            .basic_auth(&self.user, Some(&self.pass))
+    }
+
+    fn delete(&self, url: Url) -> RequestBuilder {
+        self.client
+            .delete(url)
+            .basic_auth(&self.user, Some(&self.pass))
+    }
+
     async fn send_with_error(request: RequestBuilder) -> Result<Response> {
         let response = request.send().await?;
 
@@ -449,8 +4020,8 @@ impl Client {
             Err(e) => if let Some(status) = e.status() {
                 if status.is_client_error() {
                     let data = response.text() .await?;
-                    let error = quick_xml::de::from_str(&data)?;
-                    Err(Error::ApiError(error))
+                    let error: ApiError = quick_xml::de::from_str(&data)?;
+                    Err(error.into())
                 } else {
                     Err(e.into())
                 }
@@ -461,8 +4032,176 @@ impl Client {
         }
     }
 
-    async fn request<T: DeserializeOwned + std::fmt::Debug>(&self, url: Url) -> Result<T> {
-        let data = Self::send_with_error(self.get(url)).await?.text().await?;
-        quick_xml::de::from_str(&data).map_err( | e | e.into() )
+    async fn send_and_parse<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let data = Self::send_with_error(request).await?.text().await?;
+        quick_xml::de::from_str(&data).map_err(|e| e.into())
+    }
+
+    async fn fetch<T: DeserializeOwned + std::fmt::Debug>(&self, url: Url) -> Result<T> {
+        self.send_and_parse(self.get(url)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT_META: &str = r#"<project name="openSUSE:Maintenance:1234" kind="maintenance_incident" foo="bar">
+  <title>Security update</title>
+  <description>Fixes &lt;CVE&gt;</description>
+  <person userid="alice" role="maintainer"/>
+  <lock>
+    <disable/>
+  </lock>
+  <build>
+    <enable repository="standard" arch="x86_64"/>
+    <disable/>
+  </build>
+  <repository name="standard" rebuild="local">
+    <download arch="x86_64" url="https://example.com/repo" repotype="rpmmd">
+      <archfilter>x86_64,noarch</archfilter>
+    </download>
+    <releasetarget project="openSUSE:Updates" repository="standard" trigger="maintenance"/>
+    <hostsystem project="openSUSE:Factory" repository="standard"/>
+    <path project="openSUSE:Factory" repository="standard"/>
+    <arch>x86_64</arch>
+    <arch>i586</arch>
+  </repository>
+  <repository name="images">
+    <base project="openSUSE:Factory"/>
+  </repository>
+  <maintenance>
+    <maintains project="openSUSE:Updates"/>
+  </maintenance>
+</project>"#;
+
+    #[test]
+    fn project_meta_roundtrip() {
+        let meta = ProjectMeta::from_xml(PROJECT_META).unwrap();
+        assert_eq!(meta.kind, Some(ProjectKind::MaintenanceIncident));
+        assert_eq!(meta.description, "Fixes <CVE>");
+        assert_eq!(
+            meta.repository("standard").unwrap().arches,
+            ["x86_64", "i586"]
+        );
+
+        let xml = meta.to_xml().unwrap();
+        assert!(xml.starts_with(
+            r#"<project name="openSUSE:Maintenance:1234" kind="maintenance_incident" foo="bar">"#
+        ));
+        assert!(xml.contains(r#"<repository name="standard" rebuild="local"><download arch="x86_64" url="https://example.com/repo" repotype="rpmmd">"#));
+        assert!(
+            xml.contains(r#"<hostsystem project="openSUSE:Factory" repository="standard"/><path "#)
+        );
+        assert!(xml.contains(
+            r#"<repository name="images"><base project="openSUSE:Factory"/></repository>"#
+        ));
+        assert!(xml.ends_with(
+            r#"</repository><maintenance>
+    <maintains project="openSUSE:Updates"/>
+  </maintenance></project>"#
+        ));
+
+        let reparsed = ProjectMeta::from_xml(&xml).unwrap();
+        assert_eq!(reparsed, meta);
+        assert_eq!(reparsed.to_xml().unwrap(), xml);
+
+        // Unknown elements keep their place when the element following them
+        // is removed
+        let xml = r#"<project name="a"><title/><description/><devel project="b"/><person userid="u" role="maintainer"/><repository name="r"><arch>x86_64</arch></repository></project>"#;
+        let mut meta = ProjectMeta::from_xml(xml).unwrap();
+        meta.persons.clear();
+        assert!(meta
+            .to_xml()
+            .unwrap()
+            .contains(r#"<devel project="b"/><repository name="r">"#));
+
+        // Elements added after the unknown one in schema order stay behind it
+        meta.repositories.clear();
+        meta.build = Some(Flags::default());
+        assert!(meta
+            .to_xml()
+            .unwrap()
+            .contains(r#"<devel project="b"/><build"#));
+    }
+
+    #[test]
+    fn hunk_headers() {
+        assert_eq!(parse_hunk_header("@@ -1,2 +1,3 @@"), Some((1, 2, 1, 3)));
+        assert_eq!(parse_hunk_header("@@ -0,0 +1 @@"), Some((0, 0, 1, 1)));
+        assert_eq!(
+            parse_hunk_header("@@ -10,7 +12,8 @@ fn main() {"),
+            Some((10, 7, 12, 8))
+        );
+        assert_eq!(parse_hunk_header("--- a/foo.spec"), None);
+        assert_eq!(parse_hunk_header("@@ -x +1 @@"), None);
+    }
+
+    #[test]
+    fn file_diff_hunks() {
+        let diff = FileDiff {
+            lines: 12,
+            shown: None,
+            text: "--- foo.spec\n+++ foo.spec\n@@ -1,2 +1,2 @@ %define x\n-Version: 1\n+Version: 2\n Release: 0\n@@ -0,0 +1 @@\n+new\n\\ No newline at end of file\n".to_owned(),
+        };
+        let hunks = diff.hunks();
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(
+            (
+                hunks[0].old_start,
+                hunks[0].old_lines,
+                hunks[0].new_start,
+                hunks[0].new_lines
+            ),
+            (1, 2, 1, 2)
+        );
+        assert_eq!(
+            hunks[0].lines,
+            ["-Version: 1", "+Version: 2", " Release: 0"]
+        );
+        assert_eq!(
+            (
+                hunks[1].old_start,
+                hunks[1].old_lines,
+                hunks[1].new_start,
+                hunks[1].new_lines
+            ),
+            (0, 0, 1, 1)
+        );
+        assert_eq!(hunks[1].lines, ["+new", "\\ No newline at end of file"]);
+    }
+
+    #[test]
+    fn transitive_pkgdeps() {
+        // a and b form a cycle, c and d both lead to e
+        let xml = r#"<builddepinfo>
+  <package name="a"><source>a</source><pkgdep>b</pkgdep></package>
+  <package name="b"><source>b</source><pkgdep>a</pkgdep><pkgdep>c</pkgdep><pkgdep>d</pkgdep></package>
+  <package name="c"><source>c</source><pkgdep>e</pkgdep></package>
+  <package name="d"><source>d</source><pkgdep>e</pkgdep></package>
+  <package name="e"><source>e</source></package>
+  <cycle><package>a</package><package>b</package></cycle>
+</builddepinfo>"#;
+        let info: BuildDepInfo = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(info.cycles[0].packages, ["a", "b"]);
+        assert_eq!(info.transitive_pkgdeps("a"), ["b", "c", "d", "e"]);
+        assert_eq!(info.transitive_pkgdeps("c"), ["e"]);
+        assert!(info.transitive_pkgdeps("e").is_empty());
+        assert!(info.transitive_pkgdeps("unknown").is_empty());
+    }
+
+    #[test]
+    fn package_meta_roundtrip() {
+        let xml = r#"<package name="foo" project="home:alice"><title/><description/><releasename>bar</releasename><build><disable arch="i586"/></build></package>"#;
+        let meta = PackageMeta::from_xml(xml).unwrap();
+        let out = meta.to_xml().unwrap();
+        assert!(out.contains("<releasename>bar</releasename><build>"));
+        assert_eq!(PackageMeta::from_xml(&out).unwrap(), meta);
+
+        let meta = PackageMeta::new("home:alice".to_owned(), "foo".to_owned());
+        assert_eq!(
+            PackageMeta::from_xml(&meta.to_xml().unwrap()).unwrap(),
+            meta
+        );
     }
 }